@@ -0,0 +1,149 @@
+use crate::content::{domain_matches, Content};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// What an allowlist entry is compared against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowKind {
+    /// Content ID, where `*` matches any run of characters
+    Id,
+    /// `author` metadata, case-insensitive
+    Author,
+    /// `domain` metadata, including subdomains
+    Domain,
+    /// A flag already on the content
+    Flag,
+}
+
+impl AllowKind {
+    /// Every kind
+    pub const ALL: [AllowKind; 4] = [AllowKind::Id, AllowKind::Author, AllowKind::Domain, AllowKind::Flag];
+
+    /// Name used on the command line and in entry IDs
+    pub fn name(&self) -> &'static str {
+        match self {
+            AllowKind::Id => "id",
+            AllowKind::Author => "author",
+            AllowKind::Domain => "domain",
+            AllowKind::Flag => "flag",
+        }
+    }
+}
+
+impl fmt::Display for AllowKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for AllowKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown allowlist kind '{}' (expected id, author, domain or flag)", s))
+    }
+}
+
+/// Content that always passes, whatever rules match it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowEntry {
+    /// What the pattern is compared against
+    pub kind: AllowKind,
+    /// Content ID pattern, author, domain or flag
+    pub pattern: String,
+}
+
+impl AllowEntry {
+    /// Create an entry
+    pub fn new(kind: AllowKind, pattern: &str) -> Self {
+        Self {
+            kind,
+            pattern: pattern.to_string(),
+        }
+    }
+
+    /// Identifier shown in explanations, e.g. `author:alice`
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.kind, self.pattern)
+    }
+
+    /// Whether the entry lets the content through
+    pub fn matches(&self, content: &Content) -> bool {
+        match self.kind {
+            AllowKind::Id => glob_matches(&self.pattern, &content.id),
+            AllowKind::Author => content
+                .metadata
+                .get("author")
+                .is_some_and(|author| author.trim().eq_ignore_ascii_case(&self.pattern)),
+            AllowKind::Domain => content
+                .metadata
+                .get("domain")
+                .is_some_and(|host| domain_matches(host, &self.pattern)),
+            AllowKind::Flag => content.flags.iter().any(|flag| flag == &self.pattern),
+        }
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*`: the whole text must equal the pattern
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_glob() {
+        assert!(glob_matches("rss-*", "rss-42"));
+        assert!(glob_matches("*-news-*", "bbc-news-7"));
+        assert!(glob_matches("exact", "exact"));
+        assert!(!glob_matches("exact", "exactly"));
+        assert!(!glob_matches("a*a", "a"));
+        assert!(glob_matches("*", ""));
+    }
+
+    #[test]
+    fn test_matches() {
+        let content = Content {
+            id: "mastodon-123".to_string(),
+            text: "text".to_string(),
+            view_duration: 0,
+            metadata: HashMap::from([
+                ("author".to_string(), "Alice".to_string()),
+                ("domain".to_string(), "blog.example.com".to_string()),
+            ]),
+            flags: vec!["friends".to_string()],
+            score: 0.0,
+        };
+
+        assert!(AllowEntry::new(AllowKind::Id, "mastodon-*").matches(&content));
+        assert!(AllowEntry::new(AllowKind::Author, "alice").matches(&content));
+        assert!(AllowEntry::new(AllowKind::Domain, "example.com").matches(&content));
+        assert!(AllowEntry::new(AllowKind::Flag, "friends").matches(&content));
+        assert!(!AllowEntry::new(AllowKind::Author, "bob").matches(&content));
+        assert_eq!(AllowEntry::new(AllowKind::Author, "alice").id(), "author:alice");
+    }
+}
@@ -1,6 +1,6 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Metrics for content interaction
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,10 +17,166 @@ pub struct Metrics {
     pub created_at: DateTime<Utc>,
 }
 
+/// Attention metrics aggregated over everything seen under a profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileMetrics {
+    /// Profile name
+    pub profile: String,
+    /// Total duration of attention in milliseconds
+    pub total_duration: i64,
+    /// Number of interactions under the profile
+    pub interactions: i64,
+    /// Number of distinct content items seen
+    pub items: i64,
+    /// Timestamp of last interaction
+    pub last_interaction: DateTime<Utc>,
+}
+
+/// Idle time after which the next attention event starts a new session, in seconds
+pub const DEFAULT_IDLE_GAP_SECS: i64 = 300;
+
+/// Switches between items per minute above which a session counts as fragmented
+pub const FRAGMENTED_SWITCH_RATE: f64 = 2.0;
+
+/// One stretch of attention on a content item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttentionEvent {
+    /// Content the attention was on
+    pub content_id: String,
+    /// Duration of attention in milliseconds
+    pub duration: i64,
+    /// When the attention ended
+    pub at: DateTime<Utc>,
+}
+
+impl AttentionEvent {
+    /// When the attention began
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.at - Duration::milliseconds(self.duration.max(0))
+    }
+}
+
+/// Attention events with no idle gap longer than the threshold between them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// When the first event began
+    pub start: DateTime<Utc>,
+    /// When the last event ended
+    pub end: DateTime<Utc>,
+    /// Duration of attention in milliseconds
+    pub attention: i64,
+    /// Number of events in the session
+    pub events: usize,
+    /// Number of distinct content items seen
+    pub items: usize,
+    /// Times attention moved from one item to another
+    pub switches: usize,
+}
+
+impl Session {
+    /// Time from the start of the first event to the end of the last, in milliseconds
+    pub fn length(&self) -> i64 {
+        (self.end - self.start).num_milliseconds()
+    }
+
+    /// Switches per minute of session length
+    pub fn switching_rate(&self) -> f64 {
+        // Count sessions shorter than a minute as a minute, so single bursts don't explode
+        self.switches as f64 / (self.length() as f64 / 60_000.0).max(1.0)
+    }
+
+    /// Whether attention jumped between items too often to count as focused
+    pub fn is_fragmented(&self) -> bool {
+        self.switching_rate() > FRAGMENTED_SWITCH_RATE
+    }
+}
+
+/// Group events into sessions, starting a new one whenever attention paused for longer
+/// than `idle_gap`
+///
+/// Events without any attention are ignored.
+pub fn sessions(events: &[AttentionEvent], idle_gap: Duration) -> Vec<Session> {
+    let mut events: Vec<&AttentionEvent> = events.iter().filter(|event| event.duration > 0).collect();
+    events.sort_by_key(|event| event.started_at());
+
+    let mut sessions = Vec::new();
+    let mut current: Vec<&AttentionEvent> = Vec::new();
+    // Latest end among the events in `current`
+    let mut end: Option<DateTime<Utc>> = None;
+    for event in events {
+        if end.is_some_and(|end| event.started_at() - end > idle_gap) {
+            sessions.push(session_of(&current));
+            current.clear();
+            end = None;
+        }
+        end = end.max(Some(event.at));
+        current.push(event);
+    }
+    if !current.is_empty() {
+        sessions.push(session_of(&current));
+    }
+    sessions
+}
+
+fn session_of(events: &[&AttentionEvent]) -> Session {
+    let items: HashSet<&str> = events.iter().map(|e| e.content_id.as_str()).collect();
+    Session {
+        start: events.iter().map(|e| e.started_at()).min().unwrap_or_default(),
+        end: events.iter().map(|e| e.at).max().unwrap_or_default(),
+        attention: events.iter().map(|e| e.duration.max(0)).sum(),
+        events: events.len(),
+        items: items.len(),
+        switches: events.windows(2).filter(|pair| pair[0].content_id != pair[1].content_id).count(),
+    }
+}
+
+/// Summary of how attention was split into sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    /// Idle gap that separated the sessions, in seconds
+    pub idle_gap: i64,
+    /// Sessions, oldest first
+    pub sessions: Vec<Session>,
+    /// Average session length in milliseconds
+    pub average_length: f64,
+    /// Average number of distinct items per session
+    pub items_per_session: f64,
+    /// Switches per minute across all sessions
+    pub switching_rate: f64,
+    /// Share of session time spent in fragmented sessions (0.0 to 1.0)
+    pub fragmented_share: f64,
+}
+
+impl SessionReport {
+    /// Split events into sessions and summarize them
+    pub fn new(events: &[AttentionEvent], idle_gap: Duration) -> Self {
+        let sessions = sessions(events, idle_gap);
+        let count = sessions.len().max(1) as f64;
+        let total_length: i64 = sessions.iter().map(Session::length).sum();
+        let minutes = (total_length as f64 / 60_000.0).max(1.0);
+        let fragmented_length: i64 = sessions.iter().filter(|s| s.is_fragmented()).map(Session::length).sum();
+
+        Self {
+            idle_gap: idle_gap.num_seconds(),
+            average_length: total_length as f64 / count,
+            items_per_session: sessions.iter().map(|s| s.items).sum::<usize>() as f64 / count,
+            switching_rate: sessions.iter().map(|s| s.switches).sum::<usize>() as f64 / minutes,
+            fragmented_share: if total_length > 0 {
+                fragmented_length as f64 / total_length as f64
+            } else {
+                0.0
+            },
+            sessions,
+        }
+    }
+}
+
 /// Tracks user attention metrics for content
 pub struct AttentionTracker {
     /// Map of content IDs to their metrics
     metrics: HashMap<String, Metrics>,
+    /// Attention events in the order they were tracked
+    events: Vec<AttentionEvent>,
     /// When the tracker was initialized
     start_time: DateTime<Utc>,
 }
@@ -30,14 +186,21 @@ impl AttentionTracker {
     pub fn new() -> Self {
         Self {
             metrics: HashMap::new(),
+            events: Vec::new(),
             start_time: Utc::now(),
         }
     }
 
-    /// Track focus time for specific content
-    pub fn track_focus(&mut self, content_id: &str, duration: i64) {
+    /// Track focus time for specific content, as an event ending now
+    pub fn track_focus(&mut self, content_id: &str, duration: i64) -> AttentionEvent {
         let now = Utc::now();
-        
+        let event = AttentionEvent {
+            content_id: content_id.to_string(),
+            duration,
+            at: now,
+        };
+        self.events.push(event.clone());
+
         self.metrics
             .entry(content_id.to_string())
             .and_modify(|m| {
@@ -52,6 +215,12 @@ impl AttentionTracker {
                 last_interaction: now,
                 created_at: now,
             });
+        event
+    }
+
+    /// Sessions tracked since the tracker was initialized
+    pub fn get_sessions(&self, idle_gap: Duration) -> Vec<Session> {
+        sessions(&self.events, idle_gap)
     }
 
     /// Get metrics for specific content
@@ -122,7 +291,6 @@ impl AttentionTracker {
 mod tests {
     use super::*;
     use std::thread::sleep;
-    use std::time::Duration;
 
     #[test]
     fn test_track_focus() {
@@ -139,7 +307,7 @@ mod tests {
         let mut tracker = AttentionTracker::new();
         
         tracker.track_focus("test-content", 1000);
-        sleep(Duration::from_millis(10));
+        sleep(std::time::Duration::from_millis(10));
         tracker.track_focus("test-content", 2000);
 
         let metrics = tracker.get_focus_metrics("test-content").unwrap();
@@ -172,4 +340,67 @@ mod tests {
         assert_eq!(most_interacted[0].content_id, "content-2");
         assert_eq!(most_interacted[0].interactions, 2);
     }
+
+    fn event(content_id: &str, seconds: i64, duration: i64) -> AttentionEvent {
+        AttentionEvent {
+            content_id: content_id.to_string(),
+            duration,
+            at: DateTime::from_timestamp(seconds, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_sessions_split_on_idle_gap() {
+        let events = vec![
+            event("a", 60, 60_000),
+            event("b", 90, 30_000),
+            event("a", 120, 30_000),
+            // Ten idle minutes later
+            event("c", 780, 60_000),
+        ];
+
+        let split = sessions(&events, Duration::seconds(DEFAULT_IDLE_GAP_SECS));
+        assert_eq!(split.len(), 2);
+        assert_eq!(split[0].length(), 120_000);
+        assert_eq!((split[0].events, split[0].items, split[0].switches), (3, 2, 2));
+        assert_eq!(split[0].switching_rate(), 1.0);
+        assert_eq!((split[1].items, split[1].switches), (1, 0));
+
+        // A longer gap keeps everything in one session
+        assert_eq!(sessions(&events, Duration::minutes(15)).len(), 1);
+    }
+
+    #[test]
+    fn test_session_report() {
+        // One focused minute, then a minute flicking between five items
+        let mut events = vec![event("long-read", 60, 60_000)];
+        events.extend((0..6).map(|i| event(&format!("post-{}", i % 5), 1_000 + i * 10, 10_000)));
+
+        let report = SessionReport::new(&events, Duration::seconds(60));
+        assert_eq!(report.sessions.len(), 2);
+        assert_eq!(report.items_per_session, 3.0);
+        assert!(!report.sessions[0].is_fragmented());
+        assert!(report.sessions[1].is_fragmented());
+        assert_eq!(report.fragmented_share, 0.5);
+
+        assert_eq!(SessionReport::new(&[], Duration::seconds(60)).fragmented_share, 0.0);
+    }
+
+    #[test]
+    fn test_sessions_ignore_zero_duration() {
+        // Items scrolled past in between don't count as switches or items
+        let events = vec![
+            event("a", 60, 60_000),
+            event("b", 70, 0),
+            event("c", 80, 0),
+            event("a", 120, 40_000),
+        ];
+
+        let split = sessions(&events, Duration::seconds(DEFAULT_IDLE_GAP_SECS));
+        assert_eq!(split.len(), 1);
+        assert_eq!((split[0].events, split[0].items, split[0].switches), (2, 1, 0));
+
+        // Nothing but zero-duration events makes no session at all
+        assert!(sessions(&events[1..3], Duration::seconds(60)).is_empty());
+    }
 }
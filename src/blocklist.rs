@@ -0,0 +1,293 @@
+use crate::content::{ActionType, ConditionType, Rule};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Hostnames in hosts files that never refer to a real blocked domain
+const HOSTS_IGNORED: &[&str] = &[
+    "localhost",
+    "localhost.localdomain",
+    "local",
+    "broadcasthost",
+    "ip6-localhost",
+    "ip6-loopback",
+    "0.0.0.0",
+];
+
+/// Supported blocklist formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListFormat {
+    /// One keyword per line, `#` comments
+    Plain,
+    /// Adblock Plus / uBlock network filter syntax
+    Adblock,
+    /// `/etc/hosts` style `0.0.0.0 domain` lines
+    Hosts,
+}
+
+impl FromStr for ListFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "plain" => Ok(Self::Plain),
+            "adblock" => Ok(Self::Adblock),
+            "hosts" => Ok(Self::Hosts),
+            _ => bail!("Invalid list format '{}', expected plain, adblock or hosts", s),
+        }
+    }
+}
+
+impl fmt::Display for ListFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plain => write!(f, "plain"),
+            Self::Adblock => write!(f, "adblock"),
+            Self::Hosts => write!(f, "hosts"),
+        }
+    }
+}
+
+/// An imported blocklist and the rules it owns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blocklist {
+    /// List identifier, used as the prefix of its rule IDs
+    pub id: String,
+    /// Where the list was imported from
+    pub source: String,
+    /// Format the list was parsed as
+    pub format: ListFormat,
+    /// Number of rules currently owned by the list
+    pub rule_count: i64,
+    /// When the list was last imported
+    pub imported_at: DateTime<Utc>,
+}
+
+/// Conditions parsed from a list, in file order and without duplicates
+#[derive(Debug, Default)]
+pub struct ParsedList {
+    /// Parsed conditions
+    pub conditions: Vec<ConditionType>,
+    /// Lines that were not comments but could not be converted
+    pub skipped: usize,
+}
+
+/// Outcome of importing a list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// Rules now owned by the list
+    pub imported: usize,
+    /// Rules dropped because an existing rule already has the same condition
+    pub duplicates: usize,
+    /// Rules from a previous import that are no longer in the list
+    pub removed: usize,
+    /// Lines that could not be converted into a rule
+    pub skipped: usize,
+}
+
+/// Parse a list into conditions
+pub fn parse_list(input: &str, format: ListFormat) -> ParsedList {
+    let mut parsed = ParsedList::default();
+    let mut seen = HashSet::new();
+
+    for line in input.lines() {
+        let line = line.trim();
+        let conditions = match format {
+            ListFormat::Plain => parse_plain_line(line),
+            ListFormat::Adblock => parse_adblock_line(line),
+            ListFormat::Hosts => parse_hosts_line(line),
+        };
+
+        match conditions {
+            Some(conditions) => {
+                for condition in conditions {
+                    if seen.insert(condition.key()) {
+                        parsed.conditions.push(condition);
+                    }
+                }
+            }
+            None => parsed.skipped += 1,
+        }
+    }
+
+    parsed
+}
+
+/// Build rules owned by `list_id` from parsed conditions
+pub fn rules_for_list(list_id: &str, conditions: Vec<ConditionType>, action: &ActionType) -> Vec<Rule> {
+    conditions
+        .into_iter()
+        .map(|condition| Rule {
+            id: rule_id(list_id, &condition),
+            condition,
+            action: action.clone(),
+            priority: 0,
+            schedule: None,
+        })
+        .collect()
+}
+
+/// Stable rule ID for a condition within a list, so re-imports update rules in place
+fn rule_id(list_id: &str, condition: &ConditionType) -> String {
+    format!("{}:{}", list_id, condition.key())
+}
+
+/// `Some(vec![])` for blank and comment lines, `None` for lines that can't be converted
+fn parse_plain_line(line: &str) -> Option<Vec<ConditionType>> {
+    if line.is_empty() || line.starts_with('#') {
+        return Some(Vec::new());
+    }
+    Some(vec![ConditionType::Keyword(line.to_string())])
+}
+
+fn parse_hosts_line(line: &str) -> Option<Vec<ConditionType>> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    if line.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut fields = line.split_whitespace();
+    let address = fields.next()?;
+    if !address.contains('.') && !address.contains(':') {
+        return None;
+    }
+
+    // Self-entries such as `127.0.0.1 localhost` are expected, not unsupported
+    let hosts: Vec<_> = fields
+        .map(|host| host.trim_end_matches('.').to_lowercase())
+        .filter(|host| !HOSTS_IGNORED.contains(&host.as_str()))
+        .collect();
+    if hosts.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let domains: Vec<_> = hosts
+        .into_iter()
+        .filter(|host| is_hostname(host))
+        .map(ConditionType::Domain)
+        .collect();
+
+    if domains.is_empty() {
+        None
+    } else {
+        Some(domains)
+    }
+}
+
+fn parse_adblock_line(line: &str) -> Option<Vec<ConditionType>> {
+    if line.is_empty() || line.starts_with('!') || (line.starts_with('[') && line.ends_with(']')) {
+        return Some(Vec::new());
+    }
+
+    // Exceptions and cosmetic filters have no equivalent rule
+    if line.starts_with("@@") || line.contains("##") || line.contains("#@#") || line.contains("#?#") {
+        return None;
+    }
+
+    // Regex filters: /pattern/
+    if line.len() > 2 && line.starts_with('/') && line.ends_with('/') {
+        let pattern = &line[1..line.len() - 1];
+        return Regex::new(pattern)
+            .ok()
+            .map(|_| vec![ConditionType::Regex(pattern.to_string())]);
+    }
+
+    // Drop filter options such as $third-party
+    let pattern = line.split('$').next().unwrap_or("");
+    if pattern.is_empty() {
+        return None;
+    }
+
+    // Domain anchors: ||example.com^
+    if let Some(rest) = pattern.strip_prefix("||") {
+        let host = rest.trim_end_matches('^').trim_end_matches('/');
+        if is_hostname(host) {
+            return Some(vec![ConditionType::Domain(host.to_lowercase())]);
+        }
+    }
+
+    let pattern = pattern.trim_start_matches('|').trim_end_matches('|');
+    if pattern.contains('*') || pattern.contains('^') {
+        let regex = wildcard_to_regex(pattern);
+        return Regex::new(&regex).ok().map(|_| vec![ConditionType::Regex(regex)]);
+    }
+
+    Some(vec![ConditionType::Keyword(pattern.to_string())])
+}
+
+/// Convert an adblock wildcard pattern (`*`, `^`) into a regex
+fn wildcard_to_regex(pattern: &str) -> String {
+    let mut regex = String::new();
+    for part in pattern.split_inclusive(['*', '^']) {
+        let (literal, special) = match part.chars().last() {
+            Some(c @ ('*' | '^')) => (&part[..part.len() - 1], Some(c)),
+            _ => (part, None),
+        };
+        regex.push_str(&regex::escape(literal));
+        match special {
+            Some('*') => regex.push_str(".*"),
+            Some('^') => regex.push_str(r"(?:[^\w.%-]|$)"),
+            _ => {}
+        }
+    }
+    regex
+}
+
+fn is_hostname(host: &str) -> bool {
+    !host.is_empty()
+        && host.contains('.')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain() {
+        let parsed = parse_list("# spoilers\nspoiler\n\nleak\nSpoiler\n", ListFormat::Plain);
+        assert_eq!(parsed.conditions.len(), 2);
+        assert_eq!(parsed.skipped, 0);
+    }
+
+    #[test]
+    fn test_parse_hosts() {
+        let input = "127.0.0.1 localhost\n0.0.0.0 ads.example.com tracker.example.net # ads\n::1 ip6-localhost\n0.0.0.0 not_a_host\n";
+        let parsed = parse_list(input, ListFormat::Hosts);
+        let keys: Vec<_> = parsed.conditions.iter().map(ConditionType::key).collect();
+        assert_eq!(keys, vec![
+            ConditionType::Domain("ads.example.com".to_string()).key(),
+            ConditionType::Domain("tracker.example.net".to_string()).key(),
+        ]);
+        assert_eq!(parsed.skipped, 1);
+    }
+
+    #[test]
+    fn test_parse_adblock() {
+        let input = "[Adblock Plus 2.0]\n! comment\n||doubleclick.net^\n/ba+nner/\n/ads/*.gif\nsponsored$third-party\n@@||good.com^\nexample.com##.ad\n";
+        let parsed = parse_list(input, ListFormat::Adblock);
+
+        assert!(matches!(&parsed.conditions[0], ConditionType::Domain(d) if d == "doubleclick.net"));
+        assert!(matches!(&parsed.conditions[1], ConditionType::Regex(r) if r == "ba+nner"));
+        match &parsed.conditions[2] {
+            ConditionType::Regex(r) => assert!(Regex::new(r).unwrap().is_match("/ads/banner.gif")),
+            other => panic!("unexpected condition {:?}", other),
+        }
+        assert!(matches!(&parsed.conditions[3], ConditionType::Keyword(k) if k == "sponsored"));
+        assert_eq!(parsed.skipped, 2);
+    }
+
+    #[test]
+    fn test_rule_ids_are_stable() {
+        let conditions = parse_list("Spoiler\n", ListFormat::Plain).conditions;
+        let rules = rules_for_list("spoilers", conditions, &ActionType::Filter);
+        assert_eq!(rules[0].id, "spoilers:keyword:spoiler");
+    }
+}
@@ -1,7 +1,17 @@
+use crate::allowlist::AllowEntry;
+use crate::dedup::{self, FingerprintIndex, Seen};
+use crate::features::{Comparison, TextFeature};
+use crate::lexicon::{Lexicon, LexiconScore};
+use crate::links;
+use crate::reputation::{self, Reputation};
+use crate::schedule::Schedule;
+use crate::summarize;
 use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use tokio::sync::RwLock;
 use std::sync::Arc;
 
@@ -13,11 +23,17 @@ pub struct Content {
     /// Main text content
     pub text: String,
     /// View duration in milliseconds
+    #[serde(default)]
     pub view_duration: i64,
     /// Optional metadata
+    #[serde(default)]
     pub metadata: HashMap<String, String>,
     /// Optional flags from filtering
+    #[serde(default)]
     pub flags: Vec<String>,
+    /// Ranking score accumulated from scoring rules
+    #[serde(default)]
+    pub score: f64,
 }
 
 /// Condition types for filtering rules
@@ -27,12 +43,77 @@ pub enum ConditionType {
     Keyword(String),
     /// Regular expression pattern
     Regex(String),
+    /// Domain in the `domain` metadata key, including its subdomains
+    Domain(String),
+    /// Metadata value equality (case-insensitive)
+    Metadata {
+        key: String,
+        value: String,
+    },
+    /// All conditions match
+    All(Vec<ConditionType>),
+    /// At least one condition matches
+    Any(Vec<ConditionType>),
+    /// Condition does not match
+    Not(Box<ConditionType>),
     /// Machine learning model inference
     #[serde(rename = "ml")]
     MachineLearning {
         model_id: String,
         threshold: f32,
     },
+    /// Numeric text feature compared against a value, e.g. `exclamations >= 3`
+    Feature {
+        feature: TextFeature,
+        op: Comparison,
+        value: f64,
+    },
+    /// Clickbait/outrage lexicon score of at least `threshold` (0.0 to 1.0)
+    Clickbait {
+        threshold: f64,
+    },
+    /// Some link points to one of the domains or their subdomains, as with `Domain`
+    LinksTo {
+        domains: Vec<String>,
+    },
+    /// Some link points to a host matching none of the patterns
+    LinksOutside {
+        domains: Vec<String>,
+    },
+    /// Reputation score (0.0 to 1.0) of the item's `key` metadata, e.g. its author, compared
+    /// against a value; items without that metadata never match
+    Reputation {
+        key: String,
+        op: Comparison,
+        value: f64,
+    },
+    /// Sentiment score (-1.0 to 1.0) compared against a value, e.g. `sentiment < -0.5`
+    Sentiment {
+        op: Comparison,
+        value: f64,
+    },
+    /// Profanity/toxicity lexicon score of at least `threshold` (0.0 to 1.0)
+    Toxicity {
+        threshold: f64,
+    },
+    /// Content in language `code`, detected with at least `min_confidence` (0.0 to 1.0)
+    Language {
+        code: String,
+        #[serde(default)]
+        min_confidence: f64,
+    },
+    /// Text nearly identical to an item already shown, optionally within the last `window_hours`
+    NearDuplicate {
+        threshold: f32,
+        #[serde(default)]
+        window_hours: Option<i64>,
+    },
+    /// Condition plugin registered under `name`
+    Plugin {
+        name: String,
+        #[serde(default)]
+        config: serde_json::Value,
+    },
 }
 
 /// Action types for filtering rules
@@ -48,6 +129,50 @@ pub enum ActionType {
     Flag {
         flags: Vec<String>,
     },
+    /// Remove redirect wrappers and tracking parameters from every link in the text, plus
+    /// `params` on top of the filter's list, and keep evaluating
+    CleanLinks {
+        #[serde(default)]
+        params: Vec<String>,
+    },
+    /// Replace long text with its `sentences` most representative sentences
+    Summarize {
+        sentences: usize,
+    },
+    /// Flag content as `duplicate-of:<id>` of the most similar item already shown, or
+    /// remove it when `collapse` is set
+    MarkDuplicate {
+        #[serde(default)]
+        collapse: bool,
+    },
+    /// Add `weight` to the content's score (negative to penalize) and keep evaluating
+    Score {
+        weight: f64,
+    },
+    /// Hold content back in the digest queue for `delay_hours`, tagged with `flags`
+    Quarantine {
+        delay_hours: i64,
+        #[serde(default)]
+        flags: Vec<String>,
+    },
+    /// Let the first `max` matches in a rolling window through and filter the rest
+    RateLimit {
+        max: u32,
+        window_hours: i64,
+    },
+    /// Action plugin registered under `name`
+    Plugin {
+        name: String,
+        #[serde(default)]
+        config: serde_json::Value,
+    },
+}
+
+impl ActionType {
+    /// Whether a match ends evaluation; scoring and link-cleaning rules let later rules run
+    pub fn stops_evaluation(&self) -> bool {
+        !matches!(self, ActionType::Score { .. } | ActionType::CleanLinks { .. })
+    }
 }
 
 /// Rule for content filtering
@@ -59,52 +184,599 @@ pub struct Rule {
     pub condition: ConditionType,
     /// Action to take when condition matches
     pub action: ActionType,
+    /// Evaluation priority; higher runs first, ties are ordered by ID
+    #[serde(default)]
+    pub priority: i32,
+    /// Optional schedule limiting when the rule applies
+    #[serde(default)]
+    pub schedule: Option<Schedule>,
+}
+
+impl Rule {
+    /// Whether the rule applies at the given instant
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        match &self.schedule {
+            Some(schedule) => schedule.is_active_at(at),
+            None => true,
+        }
+    }
+
+    /// Whether the rule applies right now
+    pub fn is_active(&self) -> bool {
+        self.is_active_at(Utc::now())
+    }
+
+    /// Check that the rule can be loaded into a filter
+    pub fn validate(&self) -> Result<()> {
+        for pattern in self.condition.regex_patterns() {
+            Regex::new(pattern)?;
+        }
+        if let Some(schedule) = &self.schedule {
+            schedule.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl ConditionType {
+    /// Normalized key used to detect rules with the same condition
+    ///
+    /// Keywords and domains match case-insensitively, so their keys are lowercased.
+    pub fn key(&self) -> String {
+        match self {
+            ConditionType::Keyword(keyword) => format!("keyword:{}", keyword.to_lowercase()),
+            ConditionType::Regex(pattern) => format!("regex:{}", pattern),
+            ConditionType::Domain(domain) => format!("domain:{}", domain.to_lowercase()),
+            other => serde_json::to_string(other).unwrap_or_default(),
+        }
+    }
+
+    /// This condition and all conditions nested in it, outermost first
+    pub fn walk(&self) -> Vec<&ConditionType> {
+        let mut nodes = vec![self];
+        let mut i = 0;
+        while i < nodes.len() {
+            match nodes[i] {
+                ConditionType::All(children) | ConditionType::Any(children) => nodes.extend(children.iter()),
+                ConditionType::Not(child) => nodes.push(child),
+                _ => {}
+            }
+            i += 1;
+        }
+        nodes
+    }
+
+    /// All regex patterns used by this condition
+    pub fn regex_patterns(&self) -> Vec<&str> {
+        self.walk()
+            .into_iter()
+            .filter_map(|c| match c {
+                ConditionType::Regex(pattern) => Some(pattern.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Content held back by a quarantine rule until its release time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedItem {
+    /// Queued content, with the rule's flags added
+    pub content: Content,
+    /// Rule that queued the content
+    pub rule_id: String,
+    /// When the content was queued
+    pub queued_at: DateTime<Utc>,
+    /// When the content is due for the digest
+    pub release_at: DateTime<Utc>,
+}
+
+/// Group queued items by their first flag, or `""` for unflagged items
+pub fn group_by_flag(items: &[QueuedItem]) -> BTreeMap<&str, Vec<&QueuedItem>> {
+    let mut groups: BTreeMap<&str, Vec<&QueuedItem>> = BTreeMap::new();
+    for item in items {
+        let flag = item.content.flags.first().map(String::as_str).unwrap_or("");
+        groups.entry(flag).or_default().push(item);
+    }
+    groups
+}
+
+/// Named group of rules that can be switched on as a set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// Profile name (e.g. "deep-work")
+    pub name: String,
+    /// IDs of the rules belonging to this profile
+    pub rule_ids: Vec<String>,
+}
+
+/// Result of processing one item, with the rules involved
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    /// Processed content, `None` if it was filtered out
+    pub result: Option<Content>,
+    /// IDs of the rules whose condition was evaluated, in order
+    pub evaluated: Vec<String>,
+    /// ID of the rule whose action was applied, if any
+    pub matched: Option<String>,
+    /// Whether processing the item uses up one of a rate-limit rule's allowed matches
+    pub counted: bool,
+    /// IDs of the scoring rules that matched, in order
+    pub scored: Vec<String>,
+    /// IDs of the link-cleaning rules that changed the text, in order
+    pub cleaned: Vec<String>,
+    /// Content held back by a quarantine rule, to be stored in the digest queue
+    pub queued: Option<QueuedItem>,
+    /// ID of the allowlist entry that let the item through without evaluating rules
+    pub allowed: Option<String>,
+}
+
+impl Evaluation {
+    /// Human-readable account of how the item was handled, one step per line
+    pub fn trace(&self) -> Vec<String> {
+        if let Some(entry) = &self.allowed {
+            return vec![format!("allowed by allowlist entry {}", entry)];
+        }
+
+        let mut lines = vec![format!("evaluated {} rule(s): {}", self.evaluated.len(), self.evaluated.join(", "))];
+        lines.extend(self.scored.iter().map(|id| format!("scored by rule {}", id)));
+        lines.extend(self.cleaned.iter().map(|id| format!("links cleaned by rule {}", id)));
+        let outcome = match (&self.result, &self.queued) {
+            (_, Some(item)) => format!("queued until {}", item.release_at.format("%Y-%m-%d %H:%M UTC")),
+            (None, None) => "filtered out".to_string(),
+            (Some(content), None) if content.score != 0.0 => format!("kept with score {}", content.score),
+            (Some(_), None) => "kept".to_string(),
+        };
+        match &self.matched {
+            Some(id) => lines.push(format!("matched rule {}: {}", id, outcome)),
+            None => lines.push(format!("no rule matched: {}", outcome)),
+        }
+        lines
+    }
+}
+
+/// Per-rule hit statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleStats {
+    /// Rule the statistics belong to
+    pub rule_id: String,
+    /// Items the rule's condition was evaluated against
+    pub evaluated: i64,
+    /// Items the rule matched
+    pub matched: i64,
+    /// Items the rule filtered out
+    pub filtered: i64,
+    /// Items the rule added flags to
+    pub flagged: i64,
+    /// Items whose text the rule changed
+    pub modified: i64,
+    /// Estimated attention saved by filtering, in milliseconds
+    pub time_saved: i64,
+    /// When the rule last matched
+    pub last_matched: Option<DateTime<Utc>>,
+}
+
+/// Whether `host` is `domain` or one of its subdomains
+///
+/// A leading `*.` is allowed and changes nothing; `*` on its own matches every host.
+pub fn domain_matches(host: &str, domain: &str) -> bool {
+    if domain == "*" {
+        return true;
+    }
+    let host = host.trim_end_matches('.').to_lowercase();
+    let domain = domain.strip_prefix("*.").unwrap_or(domain).trim_end_matches('.').to_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Similarity used by `MarkDuplicate` when its rule has no `NearDuplicate` condition
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.9;
+
+/// Custom condition, referenced from rules by its registered name
+#[async_trait]
+pub trait ConditionPlugin: Send + Sync {
+    /// Whether the content matches, given the rule's `config`
+    async fn matches(&self, content: &Content, config: &serde_json::Value) -> Result<bool>;
+}
+
+/// Custom action, referenced from rules by its registered name
+#[async_trait]
+pub trait ActionPlugin: Send + Sync {
+    /// Apply the action, returning `None` to filter the content out
+    async fn apply(&self, content: &Content, config: &serde_json::Value) -> Result<Option<Content>>;
+}
+
+/// Named condition and action plugins
+///
+/// Registering a name again replaces the previous plugin. Rules naming an unknown
+/// condition plugin never match; unknown action plugins leave content unchanged.
+#[derive(Default)]
+pub struct PluginRegistry {
+    conditions: std::sync::RwLock<HashMap<String, Arc<dyn ConditionPlugin>>>,
+    actions: std::sync::RwLock<HashMap<String, Arc<dyn ActionPlugin>>>,
+}
+
+impl PluginRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a condition plugin
+    pub fn register_condition(&self, name: impl Into<String>, plugin: impl ConditionPlugin + 'static) {
+        self.conditions.write().unwrap().insert(name.into(), Arc::new(plugin));
+    }
+
+    /// Register an action plugin
+    pub fn register_action(&self, name: impl Into<String>, plugin: impl ActionPlugin + 'static) {
+        self.actions.write().unwrap().insert(name.into(), Arc::new(plugin));
+    }
+
+    /// Look up a condition plugin
+    pub fn condition(&self, name: &str) -> Option<Arc<dyn ConditionPlugin>> {
+        self.conditions.read().unwrap().get(name).cloned()
+    }
+
+    /// Look up an action plugin
+    pub fn action(&self, name: &str) -> Option<Arc<dyn ActionPlugin>> {
+        self.actions.read().unwrap().get(name).cloned()
+    }
+
+    /// Whether no plugin of either kind is registered
+    pub fn is_empty(&self) -> bool {
+        self.conditions.read().unwrap().is_empty() && self.actions.read().unwrap().is_empty()
+    }
 }
 
 /// Content filter implementing rule-based filtering
 pub struct ContentFilter {
     /// Active filtering rules
     rules: HashMap<String, Rule>,
+    /// Rule IDs in evaluation order
+    order: Vec<String>,
     /// Cached regular expressions
     regex_cache: Arc<RwLock<HashMap<String, Regex>>>,
+    /// Plugins referenced by rules
+    plugins: Arc<PluginRegistry>,
+    /// Fingerprints of shown items, for duplicate detection
+    seen: Arc<std::sync::RwLock<FingerprintIndex>>,
+    /// Lexicon for clickbait conditions
+    lexicon: Arc<std::sync::RwLock<Arc<Lexicon>>>,
+    /// Entries that let content through before any rule is evaluated
+    allowlist: Arc<std::sync::RwLock<Vec<AllowEntry>>>,
+    /// Reputation scores by metadata key and value
+    reputations: Arc<std::sync::RwLock<HashMap<(String, String), f64>>>,
+    /// Query parameters link-cleaning rules remove
+    tracking_params: Arc<std::sync::RwLock<Vec<String>>>,
+    /// Times of the matches each rate-limit rule let through, oldest first
+    rate_limits: Arc<std::sync::Mutex<HashMap<String, VecDeque<DateTime<Utc>>>>>,
 }
 
 impl ContentFilter {
     /// Create a new ContentFilter instance
     pub fn new() -> Self {
+        Self::with_plugins(Arc::new(PluginRegistry::new()))
+    }
+
+    /// Create a ContentFilter that resolves plugin rules through `plugins`
+    pub fn with_plugins(plugins: Arc<PluginRegistry>) -> Self {
         Self {
             rules: HashMap::new(),
+            order: Vec::new(),
             regex_cache: Arc::new(RwLock::new(HashMap::new())),
+            plugins,
+            seen: Arc::new(std::sync::RwLock::new(FingerprintIndex::default())),
+            lexicon: Arc::new(std::sync::RwLock::new(Arc::new(Lexicon::builtin()))),
+            allowlist: Arc::new(std::sync::RwLock::new(Vec::new())),
+            reputations: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            tracking_params: Arc::new(std::sync::RwLock::new(
+                links::TRACKING_PARAMS.iter().map(|p| p.to_string()).collect(),
+            )),
+            rate_limits: Arc::new(std::sync::Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Replace the lexicon used by clickbait conditions
+    pub fn set_lexicon(&self, lexicon: Lexicon) {
+        *self.lexicon.write().unwrap() = Arc::new(lexicon);
+    }
+
+    /// Clickbait score of a text, with the terms that produced it
+    pub fn score_clickbait(&self, text: &str) -> LexiconScore {
+        let lexicon = self.lexicon.read().unwrap().clone();
+        lexicon.score(text)
+    }
+
+    /// Replace the allowlist
+    pub fn set_allowlist(&self, entries: Vec<AllowEntry>) {
+        *self.allowlist.write().unwrap() = entries;
+    }
+
+    /// Replace all known reputations
+    pub fn load_reputations(&self, reputations: Vec<Reputation>) {
+        *self.reputations.write().unwrap() = reputations
+            .into_iter()
+            .map(|r| {
+                let score = r.score();
+                ((r.key, r.value), score)
+            })
+            .collect();
+    }
+
+    /// Add or update one reputation
+    pub fn set_reputation(&self, reputation: &Reputation) {
+        self.reputations
+            .write()
+            .unwrap()
+            .insert((reputation.key.clone(), reputation.value.clone()), reputation.score());
+    }
+
+    /// Reputation score of a source, neutral if nothing is known about it
+    pub fn reputation_score(&self, key: &str, value: &str) -> f64 {
+        self.reputations
+            .read()
+            .unwrap()
+            .get(&(key.to_string(), value.to_string()))
+            .copied()
+            .unwrap_or(reputation::NEUTRAL_SCORE)
+    }
+
+    /// Replace the tracking parameters link-cleaning rules remove
+    pub fn set_tracking_params(&self, params: Vec<String>) {
+        *self.tracking_params.write().unwrap() = params;
+    }
+
+    /// Text with every link cleaned of redirect wrappers and tracking parameters
+    pub fn clean_links(&self, text: &str, extra: &[String]) -> String {
+        let mut params = self.tracking_params.read().unwrap().clone();
+        params.extend(extra.iter().cloned());
+        links::clean_text(text, &params)
+    }
+
+    /// Restore the matches rate-limit rules let through, as `(rule_id, time)` pairs
+    pub fn load_rate_limit_hits(&self, hits: Vec<(String, DateTime<Utc>)>) {
+        let mut rate_limits = self.rate_limits.lock().unwrap();
+        for (rule_id, at) in hits {
+            rate_limits.entry(rule_id).or_default().push_back(at);
+        }
+        for hits in rate_limits.values_mut() {
+            hits.make_contiguous().sort();
+        }
+    }
+
+    /// Count a match a rate-limit rule let through against its budget
+    pub fn record_rate_limit_hit(&self, rule_id: &str, at: DateTime<Utc>) {
+        let mut rate_limits = self.rate_limits.lock().unwrap();
+        let hits = rate_limits.entry(rule_id.to_string()).or_default();
+        if let Some(ActionType::RateLimit { window_hours, .. }) = self.rules.get(rule_id).map(|rule| &rule.action) {
+            let window_start = at - chrono::Duration::hours(*window_hours);
+            while hits.front().is_some_and(|&hit| hit <= window_start) {
+                hits.pop_front();
+            }
+        }
+        hits.push_back(at);
+    }
+
+    /// Add previously recorded fingerprints to the duplicate index
+    pub fn load_seen(&self, items: Vec<Seen>) {
+        let mut index = self.seen.write().unwrap();
+        for seen in items {
+            index.insert(seen);
         }
     }
 
+    /// Remember content as shown, returning its fingerprint if it is new to the index
+    pub fn record_seen(&self, content: &Content, at: DateTime<Utc>) -> Option<Seen> {
+        let fingerprint = dedup::fingerprint(&content.text)?;
+        let mut index = self.seen.write().unwrap();
+        if index.contains(&content.id) {
+            return None;
+        }
+        let seen = Seen {
+            content_id: content.id.clone(),
+            fingerprint,
+            seen_at: at,
+        };
+        index.insert(seen.clone());
+        Some(seen)
+    }
+
+    /// Forget fingerprints recorded before `cutoff`
+    pub fn prune_seen(&self, cutoff: DateTime<Utc>) {
+        self.seen.write().unwrap().prune(cutoff);
+    }
+
+    /// ID of the most similar shown item at least `threshold` similar to the content
+    fn find_duplicate(
+        &self,
+        content: &Content,
+        threshold: f32,
+        window_hours: Option<i64>,
+        at: DateTime<Utc>,
+    ) -> Option<String> {
+        let fingerprint = dedup::fingerprint(&content.text)?;
+        let since = window_hours.map(|hours| at - chrono::Duration::hours(hours));
+        let index = self.seen.read().unwrap();
+        index
+            .find(&content.id, fingerprint, threshold, since)
+            .map(|(seen, _)| seen.content_id.clone())
+    }
+
+    /// Plugin registry used by this filter
+    pub fn plugins(&self) -> &Arc<PluginRegistry> {
+        &self.plugins
+    }
+
     /// Add a new filtering rule
     pub fn add_rule(&mut self, rule: Rule) -> Result<()> {
+        self.insert_rule(rule)?;
+        self.sort_rules();
+        Ok(())
+    }
+
+    /// Insert a rule without restoring evaluation order
+    fn insert_rule(&mut self, rule: Rule) -> Result<()> {
+        if let Some(schedule) = &rule.schedule {
+            schedule.validate()?;
+        }
+
         // Pre-compile regex if needed
-        if let ConditionType::Regex(pattern) = &rule.condition {
+        for pattern in rule.condition.regex_patterns() {
             let mut cache = self.regex_cache.try_write()?;
             if !cache.contains_key(pattern) {
                 let regex = Regex::new(pattern)?;
-                cache.insert(pattern.clone(), regex);
+                cache.insert(pattern.to_string(), regex);
             }
         }
         
-        self.rules.insert(rule.id.clone(), rule);
+        let id = rule.id.clone();
+        if self.rules.insert(id.clone(), rule).is_none() {
+            self.order.push(id);
+        }
         Ok(())
     }
 
+    /// Restore evaluation order: priority descending, then ID
+    fn sort_rules(&mut self) {
+        let rules = &self.rules;
+        self.order.sort_by(|a, b| {
+            rules[b].priority.cmp(&rules[a].priority).then_with(|| a.cmp(b))
+        });
+    }
+
     /// Process content through filtering rules
+    ///
+    /// Quarantined content comes back as `None` like filtered content, and nothing keeps it
+    /// for the digest; use [`ContentFilter::process_at`] to get the held item.
     pub async fn process_content(&self, content: &Content) -> Result<Option<Content>> {
-        for rule in self.rules.values() {
-            if self.evaluate_condition(&rule.condition, content).await? {
-                return self.execute_action(&rule.action, content).await;
+        self.process_content_at(content, Utc::now()).await
+    }
+
+    /// Process content as if at the given instant, skipping rules outside their schedule
+    pub async fn process_content_at(&self, content: &Content, at: DateTime<Utc>) -> Result<Option<Content>> {
+        Ok(self.process_at(content, at).await?.result)
+    }
+
+    /// Process content as if at the given instant and report how it was handled
+    ///
+    /// Unlike [`ContentFilter::evaluate_at`] this counts rate-limit hits. A quarantined item
+    /// is in [`Evaluation::queued`], for the caller to store until its release.
+    pub async fn process_at(&self, content: &Content, at: DateTime<Utc>) -> Result<Evaluation> {
+        let evaluation = self.evaluate_at(content, at).await?;
+        if let (true, Some(rule_id)) = (evaluation.counted, &evaluation.matched) {
+            self.record_rate_limit_hit(rule_id, at);
+        }
+        Ok(evaluation)
+    }
+
+    /// Report which rules were evaluated and which one matched, without counting
+    /// rate-limit hits
+    pub async fn evaluate(&self, content: &Content) -> Result<Evaluation> {
+        self.evaluate_at(content, Utc::now()).await
+    }
+
+    /// Like [`ContentFilter::evaluate`], as if at the given instant
+    pub async fn evaluate_at(&self, content: &Content, at: DateTime<Utc>) -> Result<Evaluation> {
+        // Allowlisted content passes untouched, whatever rules would match it
+        let allowed = self.allowlist.read().unwrap().iter().find(|entry| entry.matches(content)).map(AllowEntry::id);
+        if allowed.is_some() {
+            return Ok(Evaluation {
+                result: Some(content.clone()),
+                evaluated: Vec::new(),
+                matched: None,
+                counted: false,
+                scored: Vec::new(),
+                cleaned: Vec::new(),
+                queued: None,
+                allowed,
+            });
+        }
+
+        let mut evaluated = Vec::new();
+        let mut scored = Vec::new();
+        let mut cleaned = Vec::new();
+        let mut current = content.clone();
+        let rules: Vec<&Rule> = self.ordered_rules().collect();
+        for (position, rule) in rules.iter().enumerate() {
+            if !rule.is_active_at(at) {
+                continue;
             }
+            evaluated.push(rule.id.clone());
+            if !self.evaluate_condition(&rule.condition, &current, at).await? {
+                continue;
+            }
+
+            if let ActionType::Score { weight } = rule.action {
+                current.score += weight;
+                scored.push(rule.id.clone());
+                continue;
+            }
+            if let ActionType::CleanLinks { params } = &rule.action {
+                let text = self.clean_links(&current.text, params);
+                if text != current.text {
+                    current.text = text;
+                    cleaned.push(rule.id.clone());
+                }
+                continue;
+            }
+
+            let queued = match &rule.action {
+                ActionType::Quarantine { delay_hours, flags } => {
+                    let mut content = current.clone();
+                    content.flags.extend(flags.iter().cloned());
+                    Some(QueuedItem {
+                        content,
+                        rule_id: rule.id.clone(),
+                        queued_at: at,
+                        release_at: at + chrono::Duration::hours(*delay_hours),
+                    })
+                }
+                _ => None,
+            };
+
+            let mut result = self.execute_action(rule, &current, at).await?;
+
+            // Scoring rules apply to kept content wherever they sort
+            if let Some(kept) = &mut result {
+                for later in &rules[position + 1..] {
+                    let ActionType::Score { weight } = later.action else {
+                        continue;
+                    };
+                    if !later.is_active_at(at) {
+                        continue;
+                    }
+                    evaluated.push(later.id.clone());
+                    if self.evaluate_condition(&later.condition, &current, at).await? {
+                        kept.score += weight;
+                        scored.push(later.id.clone());
+                    }
+                }
+            }
+
+            return Ok(Evaluation {
+                counted: matches!(rule.action, ActionType::RateLimit { .. }) && result.is_some(),
+                result,
+                evaluated,
+                matched: Some(rule.id.clone()),
+                scored,
+                cleaned,
+                queued,
+                allowed: None,
+            });
         }
-        Ok(Some(content.clone()))
+        Ok(Evaluation {
+            result: Some(current),
+            evaluated,
+            matched: None,
+            counted: false,
+            scored,
+            cleaned,
+            queued: None,
+            allowed: None,
+        })
     }
 
     /// Evaluate a condition against content
-    async fn evaluate_condition(&self, condition: &ConditionType, content: &Content) -> Result<bool> {
+    async fn evaluate_condition(&self, condition: &ConditionType, content: &Content, at: DateTime<Utc>) -> Result<bool> {
         match condition {
             ConditionType::Keyword(keyword) => {
                 Ok(content.text.to_lowercase().contains(&keyword.to_lowercase()))
@@ -119,17 +791,66 @@ impl ContentFilter {
                     Ok(regex.is_match(&content.text))
                 }
             }
+            ConditionType::Domain(domain) => {
+                Ok(content.metadata.get("domain").is_some_and(|host| domain_matches(host, domain)))
+            }
+            ConditionType::Metadata { key, value } => {
+                Ok(content.metadata.get(key).is_some_and(|v| v.eq_ignore_ascii_case(value)))
+            }
+            ConditionType::All(conditions) => {
+                for condition in conditions {
+                    if !Box::pin(self.evaluate_condition(condition, content, at)).await? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            ConditionType::Any(conditions) => {
+                for condition in conditions {
+                    if Box::pin(self.evaluate_condition(condition, content, at)).await? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            ConditionType::Not(condition) => Ok(!Box::pin(self.evaluate_condition(condition, content, at)).await?),
             ConditionType::MachineLearning { model_id, threshold } => {
                 // Placeholder for ML inference
                 // In a real implementation, this would load and use the model
                 Ok(false)
             }
+            ConditionType::Clickbait { threshold } => Ok(self.score_clickbait(&content.text).score >= *threshold),
+            ConditionType::Feature { feature, op, value } => Ok(op.compare(feature.measure(&content.text), *value)),
+            ConditionType::LinksTo { domains } => Ok(links::domains_of(content)
+                .iter()
+                .any(|host| domains.iter().any(|domain| domain_matches(host, domain)))),
+            ConditionType::LinksOutside { domains } => Ok(links::domains_of(content)
+                .iter()
+                .any(|host| !domains.iter().any(|domain| domain_matches(host, domain)))),
+            ConditionType::Reputation { key, op, value } => Ok(content
+                .metadata
+                .get(key)
+                .is_some_and(|source| op.compare(self.reputation_score(key, source), *value))),
+            ConditionType::Sentiment { op, value } => Ok(op.compare(crate::sentiment::sentiment(&content.text), *value)),
+            ConditionType::Toxicity { threshold } => Ok(crate::sentiment::toxicity(&content.text).score >= *threshold),
+            ConditionType::Language { code, min_confidence } => Ok(crate::lang::language_of(content)
+                .is_some_and(|(lang, confidence)| lang.eq_ignore_ascii_case(code) && confidence >= *min_confidence)),
+            ConditionType::NearDuplicate { threshold, window_hours } => {
+                Ok(self.find_duplicate(content, *threshold, *window_hours, at).is_some())
+            }
+            ConditionType::Plugin { name, config } => match self.plugins.condition(name) {
+                Some(plugin) => plugin.matches(content, config).await,
+                None => {
+                    tracing::warn!("Unknown condition plugin {}", name);
+                    Ok(false)
+                }
+            },
         }
     }
 
-    /// Execute an action on content
-    async fn execute_action(&self, action: &ActionType, content: &Content) -> Result<Option<Content>> {
-        match action {
+    /// Execute a rule's action on content
+    async fn execute_action(&self, rule: &Rule, content: &Content, at: DateTime<Utc>) -> Result<Option<Content>> {
+        match &rule.action {
             ActionType::Filter => Ok(None),
             ActionType::Modify { transform } => {
                 let mut new_content = content.clone();
@@ -143,18 +864,108 @@ impl ContentFilter {
                 new_content.flags.extend(flags.iter().cloned());
                 Ok(Some(new_content))
             }
+            ActionType::CleanLinks { params } => {
+                let mut new_content = content.clone();
+                new_content.text = self.clean_links(&content.text, params);
+                Ok(Some(new_content))
+            }
+            ActionType::Summarize { sentences } => {
+                let mut new_content = content.clone();
+                if let Some(summary) = summarize::summarize(&content.text, *sentences) {
+                    new_content.metadata.insert(
+                        summarize::ORIGINAL_LENGTH_KEY.to_string(),
+                        content.text.chars().count().to_string(),
+                    );
+                    new_content.text = summary;
+                }
+                Ok(Some(new_content))
+            }
+            ActionType::MarkDuplicate { collapse } => {
+                // Use the rule's own similarity settings to find the original
+                let (threshold, window_hours) = rule
+                    .condition
+                    .walk()
+                    .into_iter()
+                    .find_map(|c| match c {
+                        ConditionType::NearDuplicate { threshold, window_hours } => Some((*threshold, *window_hours)),
+                        _ => None,
+                    })
+                    .unwrap_or((DEFAULT_DUPLICATE_THRESHOLD, None));
+
+                match self.find_duplicate(content, threshold, window_hours, at) {
+                    Some(_) if *collapse => Ok(None),
+                    Some(original) => {
+                        let mut new_content = content.clone();
+                        new_content.flags.push(format!("duplicate-of:{}", original));
+                        Ok(Some(new_content))
+                    }
+                    None => Ok(Some(content.clone())),
+                }
+            }
+            // Removed from the feed; the caller stores `Evaluation::queued`
+            ActionType::Quarantine { .. } => Ok(None),
+            // Applied while evaluating, as it never ends evaluation
+            ActionType::Score { .. } => Ok(Some(content.clone())),
+            // Only checks the budget; processing records the hit with `record_rate_limit_hit`
+            ActionType::RateLimit { max, window_hours } => {
+                let window_start = at - chrono::Duration::hours(*window_hours);
+                let hits = self
+                    .rate_limits
+                    .lock()
+                    .unwrap()
+                    .get(&rule.id)
+                    .map_or(0, |hits| hits.iter().filter(|&&hit| hit > window_start).count());
+
+                if hits < *max as usize {
+                    Ok(Some(content.clone()))
+                } else {
+                    Ok(None)
+                }
+            }
+            ActionType::Plugin { name, config } => match self.plugins.action(name) {
+                Some(plugin) => plugin.apply(content, config).await,
+                None => {
+                    tracing::warn!("Unknown action plugin {}", name);
+                    Ok(Some(content.clone()))
+                }
+            },
         }
     }
 
-    /// Get all active rules
+    /// Rules in evaluation order
+    fn ordered_rules(&self) -> impl Iterator<Item = &Rule> {
+        self.order.iter().map(|id| &self.rules[id])
+    }
+
+    /// Get all active rules in evaluation order
     pub fn get_rules(&self) -> Vec<Rule> {
-        self.rules.values().cloned().collect()
+        self.ordered_rules().cloned().collect()
+    }
+
+    /// Get an active rule by ID
+    pub fn get_rule(&self, rule_id: &str) -> Option<Rule> {
+        self.rules.get(rule_id).cloned()
     }
 
     /// Remove a rule by ID
     pub fn remove_rule(&mut self, rule_id: &str) -> Option<Rule> {
+        self.order.retain(|id| id != rule_id);
         self.rules.remove(rule_id)
     }
+
+    /// Replace the active rule set, keeping the current one if any rule is invalid
+    pub fn replace_rules(&mut self, rules: Vec<Rule>) -> Result<()> {
+        let mut staged = Self::new();
+        for rule in rules {
+            staged.insert_rule(rule)?;
+        }
+        staged.sort_rules();
+
+        self.rules = staged.rules;
+        self.order = staged.order;
+        self.regex_cache = staged.regex_cache;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -172,6 +983,8 @@ mod tests {
                 id: "no-ads".to_string(),
                 condition: ConditionType::Keyword("sponsored".to_string()),
                 action: ActionType::Filter,
+                priority: 0,
+                schedule: None,
             }).unwrap();
 
             let content = Content {
@@ -180,6 +993,7 @@ mod tests {
                 view_duration: 0,
                 metadata: HashMap::new(),
                 flags: vec![],
+                score: 0.0,
             };
 
             assert!(filter.process_content(&content).await.unwrap().is_none());
@@ -196,6 +1010,8 @@ mod tests {
                 id: "no-urls".to_string(),
                 condition: ConditionType::Regex(r"https?://\S+".to_string()),
                 action: ActionType::Flag { flags: vec!["contains-url".to_string()] },
+                priority: 0,
+                schedule: None,
             }).unwrap();
 
             let content = Content {
@@ -204,6 +1020,7 @@ mod tests {
                 view_duration: 0,
                 metadata: HashMap::new(),
                 flags: vec![],
+                score: 0.0,
             };
 
             let processed = filter.process_content(&content).await.unwrap().unwrap();
@@ -223,6 +1040,8 @@ mod tests {
                 action: ActionType::Modify {
                     transform: "Content filtered for inappropriate language".to_string(),
                 },
+                priority: 0,
+                schedule: None,
             }).unwrap();
 
             let content = Content {
@@ -231,10 +1050,416 @@ mod tests {
                 view_duration: 0,
                 metadata: HashMap::new(),
                 flags: vec![],
+                score: 0.0,
             };
 
             let processed = filter.process_content(&content).await.unwrap().unwrap();
             assert_eq!(processed.text, "Content filtered for inappropriate language");
         });
     }
+
+    #[test]
+    fn test_scheduled_rule() {
+        use chrono::TimeZone;
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut filter = ContentFilter::new();
+
+            filter.add_rule(Rule {
+                id: "no-sports-at-work".to_string(),
+                condition: ConditionType::Keyword("football".to_string()),
+                action: ActionType::Filter,
+                priority: 0,
+                schedule: Some(Schedule {
+                    days: crate::schedule::parse_days("weekdays").unwrap(),
+                    time_ranges: vec!["09:00-17:00".parse().unwrap()],
+                    timezone: Some("UTC".to_string()),
+                    ..Default::default()
+                }),
+            }).unwrap();
+
+            let content = Content {
+                id: "test".to_string(),
+                text: "Football results".to_string(),
+                view_duration: 0,
+                metadata: HashMap::new(),
+                flags: vec![],
+                score: 0.0,
+            };
+
+            let monday_noon = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+            let saturday_noon = Utc.with_ymd_and_hms(2024, 1, 6, 12, 0, 0).unwrap();
+            assert!(filter.process_content_at(&content, monday_noon).await.unwrap().is_none());
+            assert!(filter.process_content_at(&content, saturday_noon).await.unwrap().is_some());
+        });
+    }
+
+    #[test]
+    fn test_replace_rules_keeps_old_set_on_error() {
+        let mut filter = ContentFilter::new();
+
+        filter.add_rule(Rule {
+            id: "old".to_string(),
+            condition: ConditionType::Keyword("old".to_string()),
+            action: ActionType::Filter,
+            priority: 0,
+            schedule: None,
+        }).unwrap();
+
+        let result = filter.replace_rules(vec![Rule {
+            id: "broken".to_string(),
+            condition: ConditionType::Regex("(unclosed".to_string()),
+            action: ActionType::Filter,
+            priority: 0,
+            schedule: None,
+        }]);
+
+        assert!(result.is_err());
+        assert_eq!(filter.get_rules().len(), 1);
+        assert_eq!(filter.get_rules()[0].id, "old");
+    }
+
+    #[test]
+    fn test_domain_condition() {
+        assert!(domain_matches("news.example.com", "example.com"));
+        assert!(domain_matches("Example.com.", "example.com"));
+        assert!(!domain_matches("badexample.com", "example.com"));
+        assert!(domain_matches("example.com", "*.example.com"));
+        assert!(domain_matches("news.example.com", "*.example.com"));
+        assert!(!domain_matches("badexample.com", "*.example.com"));
+        assert!(domain_matches("anything.org", "*"));
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut filter = ContentFilter::new();
+
+            filter.add_rule(Rule {
+                id: "a-filter".to_string(),
+                condition: ConditionType::Keyword("news".to_string()),
+                action: ActionType::Filter,
+                priority: 0,
+                schedule: None,
+            }).unwrap();
+            filter.add_rule(Rule {
+                id: "b-flag".to_string(),
+                condition: ConditionType::Keyword("news".to_string()),
+                action: ActionType::Flag { flags: vec!["news".to_string()] },
+                priority: 10,
+                schedule: None,
+            }).unwrap();
+
+            let content = Content {
+                id: "test".to_string(),
+                text: "Breaking news".to_string(),
+                view_duration: 0,
+                metadata: HashMap::new(),
+                flags: vec![],
+                score: 0.0,
+            };
+
+            let processed = filter.process_content(&content).await.unwrap().unwrap();
+            assert_eq!(processed.flags, vec!["news".to_string()]);
+            assert_eq!(filter.get_rules()[0].id, "b-flag");
+        });
+    }
+
+    struct WordCount;
+
+    #[async_trait]
+    impl ConditionPlugin for WordCount {
+        async fn matches(&self, content: &Content, config: &serde_json::Value) -> Result<bool> {
+            let max = config["max"].as_u64().unwrap_or(3) as usize;
+            Ok(content.text.split_whitespace().count() > max)
+        }
+    }
+
+    struct Shout;
+
+    #[async_trait]
+    impl ActionPlugin for Shout {
+        async fn apply(&self, content: &Content, _config: &serde_json::Value) -> Result<Option<Content>> {
+            let mut new_content = content.clone();
+            new_content.text = content.text.to_uppercase();
+            Ok(Some(new_content))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plugins() {
+        let plugins = Arc::new(PluginRegistry::new());
+        let mut filter = ContentFilter::with_plugins(plugins.clone());
+        filter.add_rule(Rule {
+            id: "long".to_string(),
+            condition: ConditionType::Plugin {
+                name: "word_count".to_string(),
+                config: serde_json::json!({ "max": 2 }),
+            },
+            action: ActionType::Plugin {
+                name: "shout".to_string(),
+                config: serde_json::Value::Null,
+            },
+            priority: 0,
+            schedule: None,
+        }).unwrap();
+
+        let content = Content {
+            id: "test".to_string(),
+            text: "one two three".to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+
+        // Unregistered plugins never match
+        assert_eq!(filter.evaluate(&content).await.unwrap().matched, None);
+
+        plugins.register_condition("word_count", WordCount);
+        plugins.register_action("shout", Shout);
+        let processed = filter.process_content(&content).await.unwrap().unwrap();
+        assert_eq!(processed.text, "ONE TWO THREE");
+    }
+
+    #[tokio::test]
+    async fn test_near_duplicates() {
+        let mut filter = ContentFilter::new();
+        filter.add_rule(Rule {
+            id: "reposts".to_string(),
+            condition: ConditionType::NearDuplicate { threshold: 0.85, window_hours: Some(24) },
+            action: ActionType::MarkDuplicate { collapse: false },
+            priority: 0,
+            schedule: None,
+        }).unwrap();
+
+        let item = |id: &str, text: &str| Content {
+            id: id.to_string(),
+            text: text.to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+        let original = item("a", "Scientists discover a new species of frog in the Amazon rainforest");
+        let repost = item("b", "Scientists discover a new species of frog in the Amazon rainforest, report says");
+
+        assert!(filter.process_content(&original).await.unwrap().unwrap().flags.is_empty());
+        filter.record_seen(&original, Utc::now());
+        let processed = filter.process_content(&repost).await.unwrap().unwrap();
+        assert_eq!(processed.flags, vec!["duplicate-of:a".to_string()]);
+
+        // Sightings outside the window don't count
+        filter.prune_seen(Utc::now() + chrono::Duration::seconds(1));
+        filter.load_seen(vec![Seen {
+            content_id: "a".to_string(),
+            fingerprint: dedup::fingerprint(&original.text).unwrap(),
+            seen_at: Utc::now() - chrono::Duration::hours(48),
+        }]);
+        assert!(filter.process_content(&repost).await.unwrap().unwrap().flags.is_empty());
+
+        // The window is measured from the evaluation instant, not the wall clock
+        let earlier = Utc::now() - chrono::Duration::hours(40);
+        let processed = filter.process_content_at(&repost, earlier).await.unwrap().unwrap();
+        assert_eq!(processed.flags, vec!["duplicate-of:a".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let mut filter = ContentFilter::new();
+        filter.add_rule(Rule {
+            id: "crypto".to_string(),
+            condition: ConditionType::Keyword("crypto".to_string()),
+            action: ActionType::RateLimit { max: 2, window_hours: 24 },
+            priority: 0,
+            schedule: None,
+        }).unwrap();
+
+        let content = Content {
+            id: "test".to_string(),
+            text: "crypto news".to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+        let start = Utc::now();
+        filter.load_rate_limit_hits(vec![("crypto".to_string(), start - chrono::Duration::hours(23))]);
+
+        // Evaluating alone doesn't use up the budget
+        for _ in 0..3 {
+            let evaluation = filter.evaluate_at(&content, start).await.unwrap();
+            assert!(evaluation.result.is_some() && evaluation.counted);
+        }
+        assert!(filter.process_content_at(&content, start).await.unwrap().is_some());
+        let evaluation = filter.evaluate_at(&content, start).await.unwrap();
+        assert!(evaluation.result.is_none() && !evaluation.counted);
+
+        // The restored hit leaves the window after another hour
+        let later = start + chrono::Duration::hours(1);
+        assert!(filter.process_content_at(&content, later).await.unwrap().is_some());
+        assert!(filter.process_content_at(&content, later).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_score_accumulates() {
+        let mut filter = ContentFilter::new();
+        // Scoring rules apply whether they sort before or after the rule that ends evaluation
+        for (id, keyword, action, priority) in [
+            ("boost-rust", "rust", ActionType::Score { weight: 2.0 }, 1),
+            ("flag-rust", "rust", ActionType::Flag { flags: vec!["rust".to_string()] }, 0),
+            ("penalize-hype", "amazing", ActionType::Score { weight: -0.5 }, -1),
+            ("drop-hype", "amazing", ActionType::Filter, -2),
+        ] {
+            filter.add_rule(Rule {
+                id: id.to_string(),
+                condition: ConditionType::Keyword(keyword.to_string()),
+                action,
+                priority,
+                schedule: None,
+            }).unwrap();
+        }
+
+        let content = Content {
+            id: "test".to_string(),
+            text: "Amazing Rust release".to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+
+        let evaluation = filter.evaluate(&content).await.unwrap();
+        let processed = evaluation.result.unwrap();
+        assert_eq!(processed.score, 1.5);
+        assert_eq!(processed.flags, vec!["rust".to_string()]);
+        assert_eq!(evaluation.scored, vec!["boost-rust".to_string(), "penalize-hype".to_string()]);
+        assert_eq!(evaluation.matched.as_deref(), Some("flag-rust"));
+        // Only scoring rules are checked after the match
+        assert!(!evaluation.evaluated.contains(&"drop-hype".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_quarantine() {
+        let mut filter = ContentFilter::new();
+        filter.add_rule(Rule {
+            id: "news".to_string(),
+            condition: ConditionType::Keyword("election".to_string()),
+            action: ActionType::Quarantine { delay_hours: 24, flags: vec!["news".to_string()] },
+            priority: 0,
+            schedule: None,
+        }).unwrap();
+
+        let content = Content {
+            id: "test".to_string(),
+            text: "Election results".to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+
+        let at = Utc::now();
+        assert!(filter.process_content_at(&content, at).await.unwrap().is_none());
+        let evaluation = filter.process_at(&content, at).await.unwrap();
+        assert!(evaluation.result.is_none());
+        let queued = evaluation.queued.unwrap();
+        assert_eq!(queued.release_at, at + chrono::Duration::hours(24));
+        assert_eq!(queued.content.flags, vec!["news".to_string()]);
+
+        let groups = group_by_flag(std::slice::from_ref(&queued));
+        assert_eq!(groups["news"].len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_language() {
+        let mut filter = ContentFilter::new();
+        filter.add_rule(Rule {
+            id: "german".to_string(),
+            condition: ConditionType::Language { code: "de".to_string(), min_confidence: 0.1 },
+            action: ActionType::Filter,
+            priority: 0,
+            schedule: None,
+        }).unwrap();
+
+        let mut content = Content {
+            id: "test".to_string(),
+            text: "Die Regierung hat heute einen neuen Plan für die Stadt vorgestellt".to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+        assert!(filter.process_content(&content).await.unwrap().is_none());
+
+        // A language given by the source wins over detection
+        content.metadata.insert("lang".to_string(), "en".to_string());
+        assert!(filter.process_content(&content).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_clean_links_keeps_evaluating() {
+        let mut filter = ContentFilter::new();
+        filter.set_tracking_params(vec!["utm_*".to_string()]);
+        for (id, action, priority) in [
+            ("tidy", ActionType::CleanLinks { params: vec!["ref".to_string()] }, 1),
+            ("flag-links", ActionType::Flag { flags: vec!["link".to_string()] }, 0),
+        ] {
+            filter.add_rule(Rule {
+                id: id.to_string(),
+                condition: ConditionType::Keyword("http".to_string()),
+                action,
+                priority,
+                schedule: None,
+            }).unwrap();
+        }
+
+        let content = Content {
+            id: "test".to_string(),
+            text: "New post https://example.com/p?id=3&utm_medium=social&ref=home".to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+
+        let evaluation = filter.evaluate(&content).await.unwrap();
+        let processed = evaluation.result.unwrap();
+        assert_eq!(processed.text, "New post https://example.com/p?id=3");
+        assert_eq!(processed.flags, vec!["link".to_string()]);
+        assert_eq!(evaluation.cleaned, vec!["tidy".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_overrides_rules() {
+        let mut filter = ContentFilter::new();
+        filter.add_rule(Rule {
+            id: "no-rust".to_string(),
+            condition: ConditionType::Keyword("rust".to_string()),
+            action: ActionType::Filter,
+            priority: 0,
+            schedule: None,
+        }).unwrap();
+        filter.set_allowlist(vec![AllowEntry::new(crate::allowlist::AllowKind::Author, "alice")]);
+
+        let mut content = Content {
+            id: "test".to_string(),
+            text: "Rust 2.0 is out".to_string(),
+            view_duration: 0,
+            metadata: HashMap::from([("author".to_string(), "alice".to_string())]),
+            flags: vec![],
+            score: 0.0,
+        };
+        let evaluation = filter.evaluate(&content).await.unwrap();
+        assert_eq!(evaluation.result.as_ref().unwrap().text, content.text);
+        assert_eq!(evaluation.allowed.as_deref(), Some("author:alice"));
+        assert_eq!(evaluation.trace(), vec!["allowed by allowlist entry author:alice".to_string()]);
+
+        content.metadata.insert("author".to_string(), "bob".to_string());
+        let evaluation = filter.evaluate(&content).await.unwrap();
+        assert!(evaluation.result.is_none());
+        assert_eq!(evaluation.trace().last().unwrap(), "matched rule no-rust: filtered out");
+    }
 }
@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Words per shingle when fingerprinting
+const SHINGLE_WORDS: usize = 3;
+
+/// FNV-1a, stable across builds so stored fingerprints stay comparable
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// 64-bit SimHash of the text's lowercased word shingles
+///
+/// Returns `None` for text without words, which is never considered a duplicate.
+pub fn fingerprint(text: &str) -> Option<u64> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut weights = [0i32; 64];
+    for shingle in words.windows(SHINGLE_WORDS.min(words.len())) {
+        let hash = fnv1a(shingle.join(" ").as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    Some(weights
+        .iter()
+        .enumerate()
+        .filter(|(_, &weight)| weight > 0)
+        .fold(0, |hash, (bit, _)| hash | (1 << bit)))
+}
+
+/// Fraction of equal bits between two fingerprints (1.0 = identical)
+pub fn similarity(a: u64, b: u64) -> f32 {
+    1.0 - (a ^ b).count_ones() as f32 / 64.0
+}
+
+/// Fingerprint of an item that was shown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Seen {
+    /// Content the fingerprint belongs to
+    pub content_id: String,
+    /// SimHash of the content text
+    pub fingerprint: u64,
+    /// When the content was first shown
+    pub seen_at: DateTime<Utc>,
+}
+
+/// In-memory index of seen fingerprints
+#[derive(Debug, Default)]
+pub struct FingerprintIndex {
+    items: Vec<Seen>,
+    ids: HashSet<String>,
+}
+
+impl FingerprintIndex {
+    /// Add a seen item; items already in the index keep their first sighting
+    pub fn insert(&mut self, seen: Seen) {
+        if self.ids.insert(seen.content_id.clone()) {
+            self.items.push(seen);
+        }
+    }
+
+    /// Whether the content was already recorded
+    pub fn contains(&self, content_id: &str) -> bool {
+        self.ids.contains(content_id)
+    }
+
+    /// Most similar other item seen since `since`, if at least `threshold` similar
+    ///
+    /// Ties go to the earliest sighting, so reposts point at the original.
+    pub fn find(
+        &self,
+        content_id: &str,
+        fingerprint: u64,
+        threshold: f32,
+        since: Option<DateTime<Utc>>,
+    ) -> Option<(&Seen, f32)> {
+        let mut best: Option<(&Seen, f32)> = None;
+        for seen in &self.items {
+            if seen.content_id == content_id || since.is_some_and(|since| seen.seen_at < since) {
+                continue;
+            }
+            let score = similarity(seen.fingerprint, fingerprint);
+            let better = match best {
+                None => true,
+                Some((current, current_score)) => {
+                    score > current_score || (score == current_score && seen.seen_at < current.seen_at)
+                }
+            };
+            if score >= threshold && better {
+                best = Some((seen, score));
+            }
+        }
+        best
+    }
+
+    /// Drop items seen before `cutoff`
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) {
+        self.items.retain(|seen| seen.seen_at >= cutoff);
+        self.ids = self.items.iter().map(|seen| seen.content_id.clone()).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_near_duplicates() {
+        let original = fingerprint("Scientists discover a new species of frog in the Amazon rainforest").unwrap();
+        let repost = fingerprint("BREAKING: Scientists discover a new species of frog in the Amazon rainforest!").unwrap();
+        let other = fingerprint("Local council approves budget for road repairs next spring").unwrap();
+
+        assert!(similarity(original, repost) > 0.85);
+        assert!(similarity(original, other) < 0.8);
+        assert_eq!(fingerprint("  !! "), None);
+    }
+
+    #[test]
+    fn test_index_window() {
+        let now = Utc::now();
+        let mut index = FingerprintIndex::default();
+        index.insert(Seen { content_id: "old".into(), fingerprint: 0b1111, seen_at: now - Duration::days(3) });
+        index.insert(Seen { content_id: "new".into(), fingerprint: 0b1111, seen_at: now });
+
+        assert_eq!(index.find("x", 0b1111, 0.9, None).unwrap().0.content_id, "old");
+        assert_eq!(index.find("x", 0b1111, 0.9, Some(now - Duration::days(1))).unwrap().0.content_id, "new");
+        assert!(index.find("new", !0b1111, 0.9, None).is_none());
+    }
+}
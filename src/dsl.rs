@@ -0,0 +1,941 @@
+//! Compact text syntax for rules
+//!
+//! ```text
+//! # Comments run to the end of the line
+//! rule spoilers priority 10
+//!   when text ~ /spoiler/i and meta.source = "reddit"
+//!   then flag spoiler, hidden
+//!
+//! rule ads when text contains "sponsored" or domain = "ads.example.com" then filter
+//! rule shout when not ml("toxicity") > 0.8 then modify "[quiet] {content}"
+//! rule bait when plugin("is_clickbait", "{\"min\": 3}") then plugin("dim")
+//! rule reposts when duplicate(0.9, 48) then mark-duplicate  # or: collapse
+//! rule crypto when text contains "crypto" then limit 5 per 1d  # or: per 12h
+//! rule rust when text contains "rust" then boost 2  # or: penalize 1.5
+//! rule news when domain = "news.example.com" then delay 1d flag news
+//! rule bait when clickbait >= 0.6 then delay 1d flag clickbait
+//! rule video when links_to("youtube.com", "youtu.be") then delay 12h flag video
+//! rule offsite when links_outside("wikipedia.org", "github.com") then flag external
+//! rule tidy when text contains "http" then clean-links "ref", "share_id"  # keeps evaluating
+//! rule trusted when reputation(author) >= 0.8 then boost 1
+//! rule longreads when words > 300 then summarize 3
+//! rule gloom when sentiment < -0.5 or toxicity >= 0.6 then modify "[negative] {content}"
+//! rule german when lang("de", 0.3) then flag de  # or: lang("de")
+//! rule shouty when uppercase_ratio > 0.7 and words >= 4 or exclamations >= 3 then penalize 1
+//! ```
+//!
+//! Conditions combine with `not`, `and` and `or` (in decreasing precedence) and
+//! parentheses. Plugin config is given as a JSON string. Schedules have no syntax
+//! yet and are left unset.
+
+use crate::content::{ActionType, ConditionType, Rule};
+use crate::features::{Comparison, TextFeature};
+use std::fmt::Write;
+use thiserror::Error;
+
+/// Parse error with a 1-based source position
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("line {line}, column {column}: {message}")]
+pub struct DslError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl DslError {
+    fn new(pos: Pos, message: impl Into<String>) -> Self {
+        Self {
+            line: pos.line,
+            column: pos.column,
+            message: message.into(),
+        }
+    }
+
+    /// Error message followed by the offending source line and a caret under the column
+    pub fn render(&self, src: &str) -> String {
+        let line = src.lines().nth(self.line - 1).unwrap_or("");
+        format!("{}\n  {}\n  {}^", self, line, " ".repeat(self.column - 1))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pos {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Bare word: keyword, identifier, number or `meta.<key>`
+    Word(String),
+    Str(String),
+    Regex(String),
+    LParen,
+    RParen,
+    Comma,
+    Tilde,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eof,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("'{}'", word),
+            Token::Str(s) => format!("string {:?}", s),
+            Token::Regex(_) => "regex".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::Comma => "','".to_string(),
+            Token::Tilde => "'~'".to_string(),
+            Token::Eq => "'='".to_string(),
+            Token::Ne => "'!='".to_string(),
+            Token::Lt => "'<'".to_string(),
+            Token::Le => "'<='".to_string(),
+            Token::Gt => "'>'".to_string(),
+            Token::Ge => "'>='".to_string(),
+            Token::Eof => "end of input".to_string(),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+fn tokenize(src: &str) -> Result<Vec<(Token, Pos)>, DslError> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    let mut pos = Pos { line: 1, column: 1 };
+
+    // Advance past one character, keeping track of the position
+    fn bump(chars: &mut std::iter::Peekable<std::str::Chars>, pos: &mut Pos) -> Option<char> {
+        let c = chars.next()?;
+        if c == '\n' {
+            pos.line += 1;
+            pos.column = 1;
+        } else {
+            pos.column += 1;
+        }
+        Some(c)
+    }
+
+    while let Some(&c) = chars.peek() {
+        let start = pos;
+        let token = match c {
+            c if c.is_whitespace() => {
+                bump(&mut chars, &mut pos);
+                continue;
+            }
+            '#' => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    bump(&mut chars, &mut pos);
+                }
+                continue;
+            }
+            '(' | ')' | ',' | '~' | '=' => {
+                bump(&mut chars, &mut pos);
+                match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    '~' => Token::Tilde,
+                    _ => Token::Eq,
+                }
+            }
+            '<' | '>' | '!' => {
+                bump(&mut chars, &mut pos);
+                let or_equal = chars.peek() == Some(&'=');
+                if or_equal {
+                    bump(&mut chars, &mut pos);
+                }
+                match (c, or_equal) {
+                    ('<', false) => Token::Lt,
+                    ('<', true) => Token::Le,
+                    ('>', false) => Token::Gt,
+                    ('>', true) => Token::Ge,
+                    ('!', true) => Token::Ne,
+                    _ => return Err(DslError::new(start, "unexpected character '!', did you mean '!='?")),
+                }
+            }
+            '"' => {
+                bump(&mut chars, &mut pos);
+                let mut value = String::new();
+                loop {
+                    let escape_pos = pos;
+                    match bump(&mut chars, &mut pos) {
+                        None => return Err(DslError::new(start, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') => match bump(&mut chars, &mut pos) {
+                            Some('"') => value.push('"'),
+                            Some('\\') => value.push('\\'),
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(other) => {
+                                return Err(DslError::new(escape_pos, format!("unknown escape '\\{}'", other)))
+                            }
+                            None => return Err(DslError::new(start, "unterminated string")),
+                        },
+                        Some(c) => value.push(c),
+                    }
+                }
+                Token::Str(value)
+            }
+            '/' => {
+                bump(&mut chars, &mut pos);
+                let mut pattern = String::new();
+                loop {
+                    match bump(&mut chars, &mut pos) {
+                        None | Some('\n') => return Err(DslError::new(start, "unterminated regex")),
+                        Some('/') => break,
+                        // `\/` is a literal slash; other escapes belong to the regex
+                        Some('\\') => match bump(&mut chars, &mut pos) {
+                            Some('/') => pattern.push('/'),
+                            Some(c) => {
+                                pattern.push('\\');
+                                pattern.push(c);
+                            }
+                            None => return Err(DslError::new(start, "unterminated regex")),
+                        },
+                        Some(c) => pattern.push(c),
+                    }
+                }
+
+                let mut flags = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric()) {
+                    if !"imsx".contains(c) || flags.contains(c) {
+                        return Err(DslError::new(pos, format!("invalid regex flag '{}'", c)));
+                    }
+                    flags.push(c);
+                    bump(&mut chars, &mut pos);
+                }
+                if !flags.is_empty() {
+                    pattern = format!("(?{}){}", flags, pattern);
+                }
+                Token::Regex(pattern)
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek().filter(|&&c| is_word_char(c)) {
+                    word.push(c);
+                    bump(&mut chars, &mut pos);
+                }
+                Token::Word(word)
+            }
+            other => return Err(DslError::new(start, format!("unexpected character '{}'", other))),
+        };
+        tokens.push((token, start));
+    }
+
+    tokens.push((Token::Eof, pos));
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, Pos)>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.next].0
+    }
+
+    fn pos(&self) -> Pos {
+        self.tokens[self.next].1
+    }
+
+    fn advance(&mut self) -> (Token, Pos) {
+        let token = self.tokens[self.next].clone();
+        if self.next + 1 < self.tokens.len() {
+            self.next += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T, DslError> {
+        Err(DslError::new(
+            self.pos(),
+            format!("expected {}, found {}", expected, self.peek().describe()),
+        ))
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Word(word) if word == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.at_keyword(keyword);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), DslError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", keyword))
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), DslError> {
+        if self.peek() == &token {
+            self.advance();
+            Ok(())
+        } else {
+            self.error(&token.describe())
+        }
+    }
+
+    fn string(&mut self) -> Result<String, DslError> {
+        match self.peek().clone() {
+            Token::Str(value) => {
+                self.advance();
+                Ok(value)
+            }
+            _ => self.error("a string"),
+        }
+    }
+
+    /// A bare word or a quoted string
+    fn name(&mut self, what: &str) -> Result<String, DslError> {
+        match self.peek().clone() {
+            Token::Word(value) | Token::Str(value) => {
+                self.advance();
+                Ok(value)
+            }
+            _ => self.error(what),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, what: &str) -> Result<T, DslError> {
+        match self.peek() {
+            Token::Word(word) => match word.parse() {
+                Ok(value) => {
+                    self.advance();
+                    Ok(value)
+                }
+                Err(_) => self.error(what),
+            },
+            _ => self.error(what),
+        }
+    }
+
+    fn rule(&mut self, default_id: Option<&str>) -> Result<Rule, DslError> {
+        let start = self.pos();
+        let id = if self.eat_keyword("rule") {
+            self.name("a rule id")?
+        } else if let Some(id) = default_id {
+            id.to_string()
+        } else {
+            return self.error("'rule'");
+        };
+        if id.is_empty() {
+            return Err(DslError::new(start, "rule id must not be empty"));
+        }
+
+        let priority = if self.eat_keyword("priority") {
+            self.number("an integer priority")?
+        } else {
+            0
+        };
+
+        self.expect_keyword("when")?;
+        let condition = self.or_expr()?;
+        self.expect_keyword("then")?;
+        let action = self.action()?;
+
+        Ok(Rule {
+            id,
+            condition,
+            action,
+            priority,
+            schedule: None,
+        })
+    }
+
+    fn or_expr(&mut self) -> Result<ConditionType, DslError> {
+        let mut terms = vec![self.and_expr()?];
+        while self.eat_keyword("or") {
+            terms.push(self.and_expr()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { ConditionType::Any(terms) })
+    }
+
+    fn and_expr(&mut self) -> Result<ConditionType, DslError> {
+        let mut terms = vec![self.unary()?];
+        while self.eat_keyword("and") {
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { ConditionType::All(terms) })
+    }
+
+    fn unary(&mut self) -> Result<ConditionType, DslError> {
+        if self.eat_keyword("not") {
+            return Ok(ConditionType::Not(Box::new(self.unary()?)));
+        }
+        if self.peek() == &Token::LParen {
+            self.advance();
+            let condition = self.or_expr()?;
+            self.expect(Token::RParen)?;
+            return Ok(condition);
+        }
+        self.predicate()
+    }
+
+    fn predicate(&mut self) -> Result<ConditionType, DslError> {
+        let expected = "a condition (text, domain, meta.<key>, a text feature, links_to, links_outside, reputation, clickbait, sentiment, toxicity, lang, ml, duplicate, plugin or '(')";
+        let Token::Word(subject) = self.peek().clone() else {
+            return self.error(expected);
+        };
+
+        if let Ok(feature) = subject.parse::<TextFeature>() {
+            self.advance();
+            let op = self.comparison()?;
+            let value = self.number("a number")?;
+            return Ok(ConditionType::Feature { feature, op, value });
+        }
+
+        match subject.as_str() {
+            "text" => {
+                self.advance();
+                match self.peek().clone() {
+                    Token::Tilde => {
+                        self.advance();
+                        let pos = self.pos();
+                        match self.advance().0 {
+                            Token::Regex(pattern) => match regex::Regex::new(&pattern) {
+                                Ok(_) => Ok(ConditionType::Regex(pattern)),
+                                Err(e) => Err(DslError::new(pos, format!("invalid regex: {}", e))),
+                            },
+                            token => Err(DslError::new(
+                                pos,
+                                format!("expected a regex like /pattern/i, found {}", token.describe()),
+                            )),
+                        }
+                    }
+                    Token::Word(word) if word == "contains" => {
+                        self.advance();
+                        Ok(ConditionType::Keyword(self.string()?))
+                    }
+                    _ => self.error("'~' or 'contains'"),
+                }
+            }
+            "domain" => {
+                self.advance();
+                self.expect(Token::Eq)?;
+                Ok(ConditionType::Domain(self.string()?))
+            }
+            "ml" => {
+                self.advance();
+                self.expect(Token::LParen)?;
+                let model_id = self.string()?;
+                self.expect(Token::RParen)?;
+                self.expect(Token::Gt)?;
+                let threshold = self.number("a threshold")?;
+                Ok(ConditionType::MachineLearning { model_id, threshold })
+            }
+            "clickbait" => {
+                self.advance();
+                self.expect(Token::Ge)?;
+                let threshold = self.number("a score threshold")?;
+                Ok(ConditionType::Clickbait { threshold })
+            }
+            "links_to" | "links_outside" => {
+                self.advance();
+                self.expect(Token::LParen)?;
+                let mut domains = vec![self.string()?];
+                while self.peek() == &Token::Comma {
+                    self.advance();
+                    domains.push(self.string()?);
+                }
+                self.expect(Token::RParen)?;
+                Ok(if subject == "links_to" {
+                    ConditionType::LinksTo { domains }
+                } else {
+                    ConditionType::LinksOutside { domains }
+                })
+            }
+            "reputation" => {
+                self.advance();
+                self.expect(Token::LParen)?;
+                let key = self.name("a metadata key such as author")?;
+                self.expect(Token::RParen)?;
+                let op = self.comparison()?;
+                let value = self.number("a reputation score")?;
+                Ok(ConditionType::Reputation { key, op, value })
+            }
+            "sentiment" => {
+                self.advance();
+                let op = self.comparison()?;
+                let value = self.number("a sentiment score")?;
+                Ok(ConditionType::Sentiment { op, value })
+            }
+            "toxicity" => {
+                self.advance();
+                self.expect(Token::Ge)?;
+                let threshold = self.number("a score threshold")?;
+                Ok(ConditionType::Toxicity { threshold })
+            }
+            "lang" => {
+                self.advance();
+                self.expect(Token::LParen)?;
+                let code = self.string()?;
+                let min_confidence = if self.peek() == &Token::Comma {
+                    self.advance();
+                    self.number("a confidence threshold")?
+                } else {
+                    0.0
+                };
+                self.expect(Token::RParen)?;
+                Ok(ConditionType::Language { code, min_confidence })
+            }
+            "duplicate" => {
+                self.advance();
+                self.expect(Token::LParen)?;
+                let threshold = self.number("a similarity threshold")?;
+                let window_hours = if self.peek() == &Token::Comma {
+                    self.advance();
+                    Some(self.number("a window in hours")?)
+                } else {
+                    None
+                };
+                self.expect(Token::RParen)?;
+                Ok(ConditionType::NearDuplicate { threshold, window_hours })
+            }
+            "plugin" => {
+                self.advance();
+                let (name, config) = self.plugin_call()?;
+                Ok(ConditionType::Plugin { name, config })
+            }
+            word => match word.strip_prefix("meta.") {
+                Some(key) if !key.is_empty() => {
+                    let key = key.to_string();
+                    self.advance();
+                    self.expect(Token::Eq)?;
+                    Ok(ConditionType::Metadata {
+                        key,
+                        value: self.string()?,
+                    })
+                }
+                _ => self.error(expected),
+            },
+        }
+    }
+
+    fn action(&mut self) -> Result<ActionType, DslError> {
+        if self.eat_keyword("filter") {
+            Ok(ActionType::Filter)
+        } else if self.eat_keyword("modify") {
+            Ok(ActionType::Modify {
+                transform: self.string()?,
+            })
+        } else if self.eat_keyword("flag") {
+            let mut flags = vec![self.name("a flag")?];
+            while self.peek() == &Token::Comma {
+                self.advance();
+                flags.push(self.name("a flag")?);
+            }
+            Ok(ActionType::Flag { flags })
+        } else if self.eat_keyword("clean-links") {
+            let mut params = Vec::new();
+            if matches!(self.peek(), Token::Str(_)) {
+                params.push(self.string()?);
+                while self.peek() == &Token::Comma {
+                    self.advance();
+                    params.push(self.string()?);
+                }
+            }
+            Ok(ActionType::CleanLinks { params })
+        } else if self.eat_keyword("summarize") {
+            Ok(ActionType::Summarize {
+                sentences: self.number("a number of sentences")?,
+            })
+        } else if self.eat_keyword("mark-duplicate") {
+            Ok(ActionType::MarkDuplicate { collapse: false })
+        } else if self.eat_keyword("collapse") {
+            Ok(ActionType::MarkDuplicate { collapse: true })
+        } else if self.eat_keyword("boost") {
+            Ok(ActionType::Score {
+                weight: self.number("a weight")?,
+            })
+        } else if self.eat_keyword("penalize") {
+            Ok(ActionType::Score {
+                weight: -self.number::<f64>("a weight")?,
+            })
+        } else if self.eat_keyword("delay") {
+            let delay_hours = self.duration_hours()?;
+            let mut flags = Vec::new();
+            if self.eat_keyword("flag") {
+                flags.push(self.name("a flag")?);
+                while self.peek() == &Token::Comma {
+                    self.advance();
+                    flags.push(self.name("a flag")?);
+                }
+            }
+            Ok(ActionType::Quarantine { delay_hours, flags })
+        } else if self.eat_keyword("limit") {
+            let max = self.number("a number of items")?;
+            self.expect_keyword("per")?;
+            let window_hours = self.duration_hours()?;
+            Ok(ActionType::RateLimit { max, window_hours })
+        } else if self.eat_keyword("plugin") {
+            let (name, config) = self.plugin_call()?;
+            Ok(ActionType::Plugin { name, config })
+        } else {
+            self.error("an action (filter, flag, modify, clean-links, summarize, boost, penalize, delay, mark-duplicate, collapse, limit or plugin)")
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Comparison, DslError> {
+        let op = match self.peek() {
+            Token::Lt => Comparison::Lt,
+            Token::Le => Comparison::Le,
+            Token::Gt => Comparison::Gt,
+            Token::Ge => Comparison::Ge,
+            Token::Eq => Comparison::Eq,
+            Token::Ne => Comparison::Ne,
+            _ => return self.error("a comparison (<, <=, >, >=, = or !=)"),
+        };
+        self.advance();
+        Ok(op)
+    }
+
+    /// A window like `12h` or `2d`, in hours
+    fn duration_hours(&mut self) -> Result<i64, DslError> {
+        let expected = "a window like 12h or 1d";
+        let Token::Word(word) = self.peek() else {
+            return self.error(expected);
+        };
+        let hours = if let Some(days) = word.strip_suffix('d') {
+            days.parse::<i64>().ok().and_then(|days| days.checked_mul(24))
+        } else {
+            word.strip_suffix('h').and_then(|hours| hours.parse().ok())
+        };
+        match hours {
+            Some(hours) if hours > 0 => {
+                self.advance();
+                Ok(hours)
+            }
+            _ => self.error(expected),
+        }
+    }
+
+    /// `("name")` or `("name", "<json config>")`
+    fn plugin_call(&mut self) -> Result<(String, serde_json::Value), DslError> {
+        self.expect(Token::LParen)?;
+        let name = self.string()?;
+        let config = if self.peek() == &Token::Comma {
+            self.advance();
+            let pos = self.pos();
+            let json = self.string()?;
+            serde_json::from_str(&json).map_err(|e| DslError::new(pos, format!("invalid plugin config: {}", e)))?
+        } else {
+            serde_json::Value::Null
+        };
+        self.expect(Token::RParen)?;
+        Ok((name, config))
+    }
+}
+
+/// Parse any number of rules, each starting with `rule <id>`
+pub fn parse_rules(src: &str) -> Result<Vec<Rule>, DslError> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        next: 0,
+    };
+
+    let mut rules = Vec::new();
+    while parser.peek() != &Token::Eof {
+        rules.push(parser.rule(None)?);
+    }
+    Ok(rules)
+}
+
+/// Parse a single rule; `default_id` is used when it has no `rule <id>` header
+pub fn parse_rule(src: &str, default_id: Option<&str>) -> Result<Rule, DslError> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        next: 0,
+    };
+
+    let rule = parser.rule(default_id)?;
+    if parser.peek() != &Token::Eof {
+        return parser.error("end of rule");
+    }
+    Ok(rule)
+}
+
+/// Print a rule in the DSL, on one line
+///
+/// The schedule is not printed. Empty `All`/`Any` conditions, which the parser
+/// never produces, print as `()`.
+pub fn format_rule(rule: &Rule) -> String {
+    let mut out = format!("rule {}", name(&rule.id));
+    if rule.priority != 0 {
+        write!(out, " priority {}", rule.priority).unwrap();
+    }
+    write!(out, " when {} then {}", format_condition(&rule.condition), format_action(&rule.action)).unwrap();
+    out
+}
+
+/// Print a condition in the DSL
+pub fn format_condition(condition: &ConditionType) -> String {
+    let mut out = String::new();
+    write_condition(&mut out, condition, 0);
+    out
+}
+
+/// Write a condition; `level` is 0 inside `or`, 1 inside `and` and 2 after `not`
+fn write_condition(out: &mut String, condition: &ConditionType, level: u8) {
+    let group = |out: &mut String, children: &[ConditionType], separator: &str, child_level: u8, own_level: u8| {
+        let parens = level > own_level || children.len() < 2;
+        if parens {
+            out.push('(');
+        }
+        for (i, child) in children.iter().enumerate() {
+            if i > 0 {
+                out.push_str(separator);
+            }
+            write_condition(out, child, child_level);
+        }
+        if parens {
+            out.push(')');
+        }
+    };
+
+    match condition {
+        ConditionType::Keyword(keyword) => write!(out, "text contains {}", quote(keyword)).unwrap(),
+        ConditionType::Regex(pattern) => out.push_str(&format_regex(pattern)),
+        ConditionType::Domain(domain) => write!(out, "domain = {}", quote(domain)).unwrap(),
+        ConditionType::Metadata { key, value } => write!(out, "meta.{} = {}", key, quote(value)).unwrap(),
+        ConditionType::MachineLearning { model_id, threshold } => {
+            write!(out, "ml({}) > {}", quote(model_id), threshold).unwrap()
+        }
+        ConditionType::Clickbait { threshold } => write!(out, "clickbait >= {}", threshold).unwrap(),
+        ConditionType::Feature { feature, op, value } => write!(out, "{} {} {}", feature, op, value).unwrap(),
+        ConditionType::LinksTo { domains } => write!(out, "links_to({})", quote_list(domains)).unwrap(),
+        ConditionType::LinksOutside { domains } => write!(out, "links_outside({})", quote_list(domains)).unwrap(),
+        ConditionType::Reputation { key, op, value } => write!(out, "reputation({}) {} {}", name(key), op, value).unwrap(),
+        ConditionType::Sentiment { op, value } => write!(out, "sentiment {} {}", op, value).unwrap(),
+        ConditionType::Toxicity { threshold } => write!(out, "toxicity >= {}", threshold).unwrap(),
+        ConditionType::Language { code, min_confidence } if *min_confidence > 0.0 => {
+            write!(out, "lang({}, {})", quote(code), min_confidence).unwrap()
+        }
+        ConditionType::Language { code, .. } => write!(out, "lang({})", quote(code)).unwrap(),
+        ConditionType::NearDuplicate { threshold, window_hours } => match window_hours {
+            Some(hours) => write!(out, "duplicate({}, {})", threshold, hours).unwrap(),
+            None => write!(out, "duplicate({})", threshold).unwrap(),
+        },
+        ConditionType::Plugin { name, config } => out.push_str(&format_plugin(name, config)),
+        ConditionType::All(children) => group(out, children, " and ", 1, 1),
+        ConditionType::Any(children) => group(out, children, " or ", 0, 0),
+        ConditionType::Not(child) => {
+            out.push_str("not ");
+            write_condition(out, child, 2);
+        }
+    }
+}
+
+fn format_action(action: &ActionType) -> String {
+    match action {
+        ActionType::Filter => "filter".to_string(),
+        ActionType::Modify { transform } => format!("modify {}", quote(transform)),
+        ActionType::Flag { flags } => format_flags(flags),
+        ActionType::CleanLinks { params } if params.is_empty() => "clean-links".to_string(),
+        ActionType::CleanLinks { params } => format!("clean-links {}", quote_list(params)),
+        ActionType::Summarize { sentences } => format!("summarize {}", sentences),
+        ActionType::MarkDuplicate { collapse: false } => "mark-duplicate".to_string(),
+        ActionType::MarkDuplicate { collapse: true } => "collapse".to_string(),
+        ActionType::Score { weight } if *weight < 0.0 => format!("penalize {}", -weight),
+        ActionType::Score { weight } => format!("boost {}", weight),
+        ActionType::Quarantine { delay_hours, flags } if flags.is_empty() => format!("delay {}", hours(*delay_hours)),
+        ActionType::Quarantine { delay_hours, flags } => format!("delay {} {}", hours(*delay_hours), format_flags(flags)),
+        ActionType::RateLimit { max, window_hours } => format!("limit {} per {}", max, hours(*window_hours)),
+        ActionType::Plugin { name, config } => format_plugin(name, config),
+    }
+}
+
+fn format_flags(flags: &[String]) -> String {
+    format!("flag {}", flags.iter().map(|f| name(f)).collect::<Vec<_>>().join(", "))
+}
+
+/// `2d` for whole days, otherwise `36h`
+fn hours(hours: i64) -> String {
+    if hours % 24 == 0 {
+        format!("{}d", hours / 24)
+    } else {
+        format!("{}h", hours)
+    }
+}
+
+fn format_plugin(name: &str, config: &serde_json::Value) -> String {
+    if config.is_null() {
+        format!("plugin({})", quote(name))
+    } else {
+        format!("plugin({}, {})", quote(name), quote(&config.to_string()))
+    }
+}
+
+/// `text ~ /pattern/flags`, turning a leading `(?flags)` group back into suffix flags
+fn format_regex(pattern: &str) -> String {
+    let (body, flags) = match pattern.strip_prefix("(?").and_then(|rest| rest.split_once(')')) {
+        Some((flags, body)) if !flags.is_empty() && flags.chars().all(|c| "imsx".contains(c)) => (body, flags),
+        _ => (pattern, ""),
+    };
+
+    let mut out = String::from("text ~ /");
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                out.push('\\');
+                if let Some(next) = chars.next() {
+                    out.push(next);
+                }
+            }
+            '/' => out.push_str("\\/"),
+            c => out.push(c),
+        }
+    }
+    out.push('/');
+    out.push_str(flags);
+    out
+}
+
+/// A bare word if it lexes as one, otherwise a quoted string
+fn name(value: &str) -> String {
+    if !value.is_empty() && value.chars().all(is_word_char) {
+        value.to_string()
+    } else {
+        quote(value)
+    }
+}
+
+/// `"a", "b"`
+fn quote_list(values: &[String]) -> String {
+    values.iter().map(|v| quote(v)).collect::<Vec<_>>().join(", ")
+}
+
+fn quote(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rule() {
+        let rule = parse_rule(
+            r#"when text ~ /spoiler/i and meta.source = "reddit" then flag spoiler, "hide me""#,
+            Some("spoilers"),
+        )
+        .unwrap();
+
+        assert_eq!(rule.id, "spoilers");
+        match &rule.condition {
+            ConditionType::All(terms) => {
+                assert!(matches!(&terms[0], ConditionType::Regex(p) if p == "(?i)spoiler"));
+                assert!(matches!(&terms[1], ConditionType::Metadata { key, value } if key == "source" && value == "reddit"));
+            }
+            other => panic!("unexpected condition {:?}", other),
+        }
+        assert!(matches!(&rule.action, ActionType::Flag { flags } if flags == &["spoiler", "hide me"]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let src = r#"
+            # precedence: not > and > or
+            rule ads priority -2 when text contains "sponsored" or domain = "ads.example.com" and not meta.lang = "en" then filter
+            rule "my rule" when (text ~ /a\/b/ or ml("toxicity") > 0.8) and text contains "x" then modify "[{content}]"
+            rule bait when plugin("is_clickbait", "{\"min\":3}") then plugin("dim")
+            rule reposts when duplicate(0.9, 48) then collapse
+            rule crypto when text contains "crypto" then limit 5 per 36h
+            rule hype when text contains "amazing" then penalize 0.5
+            rule news when domain = "news.example.com" then delay 24h flag news
+            rule bait when clickbait >= 0.6 then penalize 2
+            rule shouty when uppercase_ratio>0.7 and (words >= 4 or exclamations != 0) then penalize 1
+            rule foreign when not (lang("en") or lang("de", 0.3)) then filter
+            rule gloom when sentiment < -0.5 or toxicity >= 0.6 then flag negative
+            rule longreads when words > 300 then summarize 3
+            rule offsite when links_to("*.tiktok.com") or links_outside("*.wikipedia.org", "github.com") then flag external
+            rule tidy when text contains "http" then clean-links "ref", "share_id"
+            rule distrusted when reputation(source) < 0.2 and not reputation(author) >= 0.8 then delay 1d
+        "#;
+        let rules = parse_rules(src).unwrap();
+        assert_eq!(rules.len(), 15);
+        assert_eq!(rules[0].priority, -2);
+
+        let printed: Vec<String> = rules.iter().map(format_rule).collect();
+        assert_eq!(
+            printed[0],
+            r#"rule ads priority -2 when text contains "sponsored" or domain = "ads.example.com" and not meta.lang = "en" then filter"#
+        );
+        assert_eq!(
+            printed[1],
+            r#"rule "my rule" when (text ~ /a\/b/ or ml("toxicity") > 0.8) and text contains "x" then modify "[{content}]""#
+        );
+        assert_eq!(printed[2], r#"rule bait when plugin("is_clickbait", "{\"min\":3}") then plugin("dim")"#);
+        assert_eq!(printed[3], "rule reposts when duplicate(0.9, 48) then collapse");
+        assert_eq!(printed[4], r#"rule crypto when text contains "crypto" then limit 5 per 36h"#);
+        assert_eq!(printed[5], r#"rule hype when text contains "amazing" then penalize 0.5"#);
+        assert_eq!(printed[6], r#"rule news when domain = "news.example.com" then delay 1d flag news"#);
+        assert_eq!(printed[7], "rule bait when clickbait >= 0.6 then penalize 2");
+        assert_eq!(
+            printed[8],
+            "rule shouty when uppercase_ratio > 0.7 and (words >= 4 or exclamations != 0) then penalize 1"
+        );
+        assert_eq!(printed[9], r#"rule foreign when not (lang("en") or lang("de", 0.3)) then filter"#);
+        assert_eq!(printed[10], "rule gloom when sentiment < -0.5 or toxicity >= 0.6 then flag negative");
+        assert_eq!(printed[11], "rule longreads when words > 300 then summarize 3");
+        assert_eq!(
+            printed[12],
+            r#"rule offsite when links_to("*.tiktok.com") or links_outside("*.wikipedia.org", "github.com") then flag external"#
+        );
+        assert_eq!(printed[13], r#"rule tidy when text contains "http" then clean-links "ref", "share_id""#);
+        assert_eq!(
+            printed[14],
+            "rule distrusted when reputation(source) < 0.2 and not reputation(author) >= 0.8 then delay 1d"
+        );
+
+        let reparsed = parse_rules(&printed.join("\n")).unwrap();
+        assert_eq!(reparsed.iter().map(format_rule).collect::<Vec<_>>(), printed);
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let err = parse_rule("when text ~ /x/ and\n  domain \"a.com\" then filter", Some("r")).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 10));
+        assert_eq!(err.message, "expected '=', found string \"a.com\"");
+        assert!(err.render("when text ~ /x/ and\n  domain \"a.com\" then filter").ends_with("\n           ^"));
+
+        let err = parse_rule("when text ~ /(/ then filter", Some("r")).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 13));
+
+        let err = parse_rule("when text contains \"x\" then filter", None).unwrap_err();
+        assert_eq!(err.message, "expected 'rule', found 'when'");
+
+        let err = parse_rule("rule a when text contains \"x", None).unwrap_err();
+        assert_eq!((err.column, err.message.as_str()), (27, "unterminated string"));
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Numeric measurement of a text's shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextFeature {
+    /// Number of characters
+    Chars,
+    /// Number of whitespace-separated words
+    Words,
+    /// Share of letters that are uppercase (0.0 to 1.0)
+    UppercaseRatio,
+    /// Number of `!` characters
+    Exclamations,
+    /// Share of non-whitespace characters that are emoji (0.0 to 1.0)
+    EmojiDensity,
+    /// Number of links (`http://`, `https://` or `www.` words)
+    Urls,
+    /// Number of `#hashtag` words
+    Hashtags,
+}
+
+impl TextFeature {
+    /// Every feature
+    pub const ALL: [TextFeature; 7] = [
+        TextFeature::Chars,
+        TextFeature::Words,
+        TextFeature::UppercaseRatio,
+        TextFeature::Exclamations,
+        TextFeature::EmojiDensity,
+        TextFeature::Urls,
+        TextFeature::Hashtags,
+    ];
+
+    /// Name used in rules
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextFeature::Chars => "chars",
+            TextFeature::Words => "words",
+            TextFeature::UppercaseRatio => "uppercase_ratio",
+            TextFeature::Exclamations => "exclamations",
+            TextFeature::EmojiDensity => "emoji_density",
+            TextFeature::Urls => "urls",
+            TextFeature::Hashtags => "hashtags",
+        }
+    }
+
+    /// Whether the feature is a ratio between 0.0 and 1.0 rather than a count
+    pub fn is_ratio(&self) -> bool {
+        matches!(self, TextFeature::UppercaseRatio | TextFeature::EmojiDensity)
+    }
+
+    /// Measure the feature on a text
+    pub fn measure(&self, text: &str) -> f64 {
+        match self {
+            TextFeature::Chars => text.chars().count() as f64,
+            TextFeature::Words => text.split_whitespace().count() as f64,
+            TextFeature::UppercaseRatio => {
+                let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+                let upper = text.chars().filter(|c| c.is_uppercase()).count();
+                ratio(upper, letters)
+            }
+            TextFeature::Exclamations => text.matches('!').count() as f64,
+            TextFeature::EmojiDensity => {
+                let visible = text.chars().filter(|c| !c.is_whitespace()).count();
+                ratio(text.chars().filter(|&c| is_emoji(c)).count(), visible)
+            }
+            TextFeature::Urls => text
+                .split_whitespace()
+                .filter(|w| {
+                    let w = w.trim_start_matches(['(', '<', '"', '\'']).to_lowercase();
+                    w.starts_with("http://") || w.starts_with("https://") || w.starts_with("www.")
+                })
+                .count() as f64,
+            TextFeature::Hashtags => text
+                .split_whitespace()
+                .filter(|w| w.strip_prefix('#').and_then(|tag| tag.chars().next()).is_some_and(char::is_alphanumeric))
+                .count() as f64,
+        }
+    }
+}
+
+impl fmt::Display for TextFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TextFeature {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|feature| feature.name() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown text feature '{}'", s))
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// Pictographic emoji, symbols and regional indicators
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, transport, flags, supplemental symbols
+        | 0x2600..=0x27BF // miscellaneous symbols and dingbats
+        | 0x2B00..=0x2BFF // arrows and stars such as ⭐
+    )
+}
+
+/// Comparison between a measured feature and a rule's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Comparison {
+    /// Compare `left` against `right`
+    pub fn compare(&self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+            Comparison::Eq => left == right,
+            Comparison::Ne => left != right,
+        }
+    }
+
+    /// Operator symbol
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+            Comparison::Eq => "=",
+            Comparison::Ne => "!=",
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "<" | "lt" => Ok(Comparison::Lt),
+            "<=" | "le" => Ok(Comparison::Le),
+            ">" | "gt" => Ok(Comparison::Gt),
+            ">=" | "ge" => Ok(Comparison::Ge),
+            "=" | "==" | "eq" => Ok(Comparison::Eq),
+            "!=" | "ne" => Ok(Comparison::Ne),
+            _ => anyhow::bail!("Unknown comparison '{}'", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let text = "YOU WON'T BELIEVE this!!! 🔥🔥 https://t.co/x #wow #viral";
+
+        assert_eq!(TextFeature::Words.measure(text), 8.0);
+        assert_eq!(TextFeature::Exclamations.measure(text), 3.0);
+        assert_eq!(TextFeature::Urls.measure(text), 1.0);
+        assert_eq!(TextFeature::Hashtags.measure(text), 2.0);
+        assert!(TextFeature::UppercaseRatio.measure(text) > 0.3);
+        assert!(TextFeature::EmojiDensity.measure(text) > 0.0);
+        assert_eq!(TextFeature::UppercaseRatio.measure("123"), 0.0);
+        assert_eq!(TextFeature::Chars.measure("héllo"), 5.0);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("emoji_density".parse::<TextFeature>().unwrap(), TextFeature::EmojiDensity);
+        assert!("length".parse::<TextFeature>().is_err());
+        assert!(">=".parse::<Comparison>().unwrap().compare(3.0, 3.0));
+        assert!(!"!=".parse::<Comparison>().unwrap().compare(3.0, 3.0));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::content::{Content, ContentFilter, Rule};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Whether content should survive filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// Content is returned (possibly modified or flagged)
+    Kept,
+    /// Content is removed
+    Filtered,
+}
+
+/// Expected result of processing a fixture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expectation {
+    /// Whether the content is kept or filtered
+    pub outcome: Outcome,
+    /// Exact set of flags on kept content, if checked
+    #[serde(default)]
+    pub flags: Option<Vec<String>>,
+    /// Exact text of kept content, if checked
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+/// Sample content with its expected outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Case name (defaults to the content ID)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Content to process
+    pub content: Content,
+    /// Expected result
+    pub expect: Expectation,
+    /// Instant to evaluate schedules at (defaults to now)
+    #[serde(default)]
+    pub at: Option<DateTime<Utc>>,
+}
+
+impl TestCase {
+    /// Display name of the case
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.content.id)
+    }
+}
+
+/// A fixture file: test cases plus optionally the rules to test them against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestSuite {
+    /// Rules to test instead of the stored rule set
+    #[serde(default)]
+    pub rules: Option<Vec<Rule>>,
+    /// Test cases
+    pub cases: Vec<TestCase>,
+}
+
+impl TestSuite {
+    /// Parse a fixture file, either a suite object or a bare array of cases
+    pub fn from_json(json: &str) -> Result<Self> {
+        match serde_json::from_str::<Self>(json) {
+            Ok(suite) => Ok(suite),
+            Err(suite_err) => match serde_json::from_str::<Vec<TestCase>>(json) {
+                Ok(cases) => Ok(Self { rules: None, cases }),
+                Err(_) => Err(suite_err.into()),
+            },
+        }
+    }
+}
+
+/// Result of running one test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    /// Case name
+    pub name: String,
+    /// Differences between expected and actual results; empty when the case passed
+    pub failures: Vec<String>,
+}
+
+impl CaseResult {
+    /// Whether the case passed
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Run test cases through a filter without changing its state, so live rate limits
+/// aren't used up
+pub async fn run_cases(filter: &ContentFilter, cases: &[TestCase]) -> Result<Vec<CaseResult>> {
+    run(filter, cases, false).await
+}
+
+/// Run cases in order, counting rate-limit hits between them when `count_hits` is set
+async fn run(filter: &ContentFilter, cases: &[TestCase], count_hits: bool) -> Result<Vec<CaseResult>> {
+    let mut results = Vec::with_capacity(cases.len());
+    for case in cases {
+        let at = case.at.unwrap_or_else(Utc::now);
+        let processed = if count_hits {
+            filter.process_content_at(&case.content, at).await?
+        } else {
+            filter.evaluate_at(&case.content, at).await?.result
+        };
+        results.push(CaseResult {
+            name: case.name().to_string(),
+            failures: compare(&case.expect, processed.as_ref()),
+        });
+    }
+    Ok(results)
+}
+
+/// Run a suite against its own rules, or against `filter` when it has none
+///
+/// A suite's own rules start with fresh rate limits that its cases use up in order;
+/// cases run against `filter` leave it unchanged.
+pub async fn run_suite(filter: &ContentFilter, suite: &TestSuite) -> Result<Vec<CaseResult>> {
+    match &suite.rules {
+        Some(rules) => {
+            let mut own = ContentFilter::with_plugins(filter.plugins().clone());
+            own.replace_rules(rules.clone())?;
+            run(&own, &suite.cases, true).await
+        }
+        None => run_cases(filter, &suite.cases).await,
+    }
+}
+
+fn compare(expect: &Expectation, processed: Option<&Content>) -> Vec<String> {
+    let mut failures = Vec::new();
+
+    let processed = match (expect.outcome, processed) {
+        (Outcome::Filtered, None) => return failures,
+        (Outcome::Filtered, Some(_)) => {
+            failures.push("outcome: expected filtered, got kept".to_string());
+            return failures;
+        }
+        (Outcome::Kept, None) => {
+            failures.push("outcome: expected kept, got filtered".to_string());
+            return failures;
+        }
+        (Outcome::Kept, Some(processed)) => processed,
+    };
+
+    if let Some(flags) = &expect.flags {
+        let expected: BTreeSet<_> = flags.iter().collect();
+        let actual: BTreeSet<_> = processed.flags.iter().collect();
+        if expected != actual {
+            failures.push(format!("flags: expected {:?}, got {:?}", expected, actual));
+        }
+    }
+
+    if let Some(text) = &expect.text {
+        if text != &processed.text {
+            failures.push(format!("text: expected {:?}, got {:?}", text, processed.text));
+        }
+    }
+
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUITE: &str = r#"{
+        "rules": [
+            { "id": "no-ads", "condition": { "Keyword": "sponsored" }, "action": "Filter" },
+            { "id": "spoilers", "condition": { "Keyword": "ending" }, "action": { "Flag": { "flags": ["spoiler"] } } }
+        ],
+        "cases": [
+            { "content": { "id": "ad", "text": "A sponsored post" }, "expect": { "outcome": "filtered" } },
+            { "name": "spoiler", "content": { "id": "s", "text": "The ending" }, "expect": { "outcome": "kept", "flags": ["spoiler"] } },
+            { "name": "wrong", "content": { "id": "w", "text": "Plain" }, "expect": { "outcome": "kept", "text": "Other" } }
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_run_suite() -> Result<()> {
+        let suite = TestSuite::from_json(SUITE)?;
+        let results = run_suite(&ContentFilter::new(), &suite).await?;
+
+        assert_eq!(results[0].name, "ad");
+        assert!(results[0].passed());
+        assert!(results[1].passed());
+        assert_eq!(results[2].failures, vec![r#"text: expected "Other", got "Plain""#.to_string()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bare_case_array() -> Result<()> {
+        let suite = TestSuite::from_json(
+            r#"[{ "content": { "id": "a", "text": "hello" }, "expect": { "outcome": "filtered" } }]"#,
+        )?;
+        let results = run_suite(&ContentFilter::new(), &suite).await?;
+
+        assert_eq!(results[0].failures, vec!["outcome: expected filtered, got kept".to_string()]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_live_filter_keeps_rate_limits() -> Result<()> {
+        use crate::content::{ActionType, ConditionType, Rule};
+
+        let mut filter = ContentFilter::new();
+        filter.add_rule(Rule {
+            id: "once".to_string(),
+            condition: ConditionType::Keyword("crypto".to_string()),
+            action: ActionType::RateLimit { max: 1, window_hours: 24 },
+            priority: 0,
+            schedule: None,
+        })?;
+        let suite = TestSuite::from_json(
+            r#"[{ "content": { "id": "a", "text": "crypto" }, "expect": { "outcome": "kept" } },
+                { "content": { "id": "b", "text": "crypto" }, "expect": { "outcome": "kept" } }]"#,
+        )?;
+
+        let results = run_suite(&filter, &suite).await?;
+        assert!(results.iter().all(CaseResult::passed));
+
+        // The one allowed match is still available to real content
+        let content = suite.cases[0].content.clone();
+        assert!(filter.process_content(&content).await?.is_some());
+        assert!(filter.process_content(&content).await?.is_none());
+        Ok(())
+    }
+}
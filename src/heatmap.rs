@@ -0,0 +1,172 @@
+use crate::attention::AttentionEvent;
+use crate::schedule::to_local;
+use chrono::{Datelike, Duration, FixedOffset, NaiveDateTime, Timelike};
+use serde::Serialize;
+
+/// Row labels, Monday first
+pub const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Characters from no attention to the busiest hour
+const SHADES: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+/// Attention duration by day of the week and hour of the day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Heatmap {
+    /// Row labels of `duration`
+    pub days: [&'static str; 7],
+    /// Attention in milliseconds, indexed by day (Monday first) and hour
+    pub duration: [[i64; 24]; 7],
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            days: DAYS,
+            duration: [[0; 24]; 7],
+        }
+    }
+}
+
+impl Heatmap {
+    /// Sum attention events into the hours they cover, in the time at `offset`
+    /// (system local time if `None`)
+    ///
+    /// Events spanning an hour boundary are split between the hours.
+    pub fn from_events(events: &[AttentionEvent], offset: Option<FixedOffset>) -> Self {
+        let mut heatmap = Self::default();
+        for event in events {
+            heatmap.add(to_local(event.started_at(), offset), to_local(event.at, offset));
+        }
+        heatmap
+    }
+
+    fn add(&mut self, start: NaiveDateTime, end: NaiveDateTime) {
+        let mut at = start;
+        while at < end {
+            let hour_start = at.with_minute(0).and_then(|t| t.with_second(0)).and_then(|t| t.with_nanosecond(0));
+            let next = hour_start.map_or(end, |t| (t + Duration::hours(1)).min(end));
+            self.duration[at.weekday().num_days_from_monday() as usize][at.hour() as usize] +=
+                (next - at).num_milliseconds();
+            at = next;
+        }
+    }
+
+    /// Total attention in milliseconds
+    pub fn total(&self) -> i64 {
+        self.duration.iter().flatten().sum()
+    }
+
+    /// Busiest `(day, hour, milliseconds)`, if any attention was recorded
+    pub fn peak(&self) -> Option<(&'static str, usize, i64)> {
+        let (day, hour, duration) = (0..7)
+            .flat_map(|day| (0..24).map(move |hour| (day, hour)))
+            .map(|(day, hour)| (day, hour, self.duration[day][hour]))
+            .max_by_key(|(day, hour, duration)| (*duration, std::cmp::Reverse((*day, *hour))))?;
+        (duration > 0).then_some((DAYS[day], hour, duration))
+    }
+
+    /// ASCII heatmap with one row per day and one column per hour, shaded relative to
+    /// the busiest hour
+    pub fn render(&self) -> String {
+        let max = self.duration.iter().flatten().copied().max().unwrap_or(0);
+        let mut out = String::from("    ");
+        for hour in 0..24 {
+            out.push_str(&format!("{:>3}", hour));
+        }
+        out.push_str("  total\n");
+
+        for (day, row) in DAYS.iter().zip(&self.duration) {
+            out.push_str(day);
+            out.push(' ');
+            for &duration in row {
+                let shade = shade(duration, max);
+                out.push_str(&format!(" {}{}", shade, shade));
+            }
+            out.push_str(&format!("  {}m\n", row.iter().sum::<i64>() / 60_000));
+        }
+
+        out.push_str(&format!(
+            "\nScale: blank is none, '{}' to '{}' is up to {}m in an hour",
+            SHADES[1],
+            SHADES[SHADES.len() - 1],
+            max / 60_000
+        ));
+        out
+    }
+
+    /// CSV with a header row of hours and one row per day, in milliseconds
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("day");
+        for hour in 0..24 {
+            out.push_str(&format!(",{}", hour));
+        }
+        out.push('\n');
+        for (day, row) in DAYS.iter().zip(&self.duration) {
+            out.push_str(day);
+            for duration in row {
+                out.push_str(&format!(",{}", duration));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn shade(duration: i64, max: i64) -> char {
+    if duration <= 0 || max <= 0 {
+        return SHADES[0];
+    }
+    // Any attention at all shows up as at least the lightest shade
+    let levels = (SHADES.len() - 1) as f64;
+    let level = (duration as f64 / max as f64 * levels).ceil() as usize;
+    SHADES[level.clamp(1, SHADES.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    fn event(at: &str, duration: i64) -> AttentionEvent {
+        AttentionEvent {
+            content_id: "test".to_string(),
+            duration,
+            at: at.parse::<DateTime<Utc>>().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_from_events_splits_hours() {
+        // 2024-06-01 is a Saturday; 20 minutes from 21:50 to 22:10 UTC
+        let events = vec![event("2024-06-01T22:10:00Z", 1_200_000), event("2024-06-03T09:00:00Z", 60_000)];
+
+        let heatmap = Heatmap::from_events(&events, FixedOffset::east_opt(0));
+        assert_eq!(heatmap.duration[5][21], 600_000);
+        assert_eq!(heatmap.duration[5][22], 600_000);
+        assert_eq!(heatmap.duration[0][8], 60_000);
+        assert_eq!(heatmap.total(), 1_260_000);
+        assert_eq!(heatmap.peak(), Some(("Sat", 21, 600_000)));
+
+        // Two hours ahead, the same attention lands two hours later
+        let shifted = Heatmap::from_events(&events, FixedOffset::east_opt(2 * 3600));
+        assert_eq!(shifted.duration[5][23], 600_000);
+        assert_eq!(shifted.duration[6][0], 600_000);
+    }
+
+    #[test]
+    fn test_render_and_csv() {
+        let mut heatmap = Heatmap::default();
+        heatmap.duration[2][9] = 3_600_000;
+        heatmap.duration[2][10] = 60_000;
+
+        let rendered = heatmap.render();
+        let wed = rendered.lines().find(|line| line.starts_with("Wed")).unwrap();
+        assert!(wed.contains(" @@ .."));
+        assert!(wed.ends_with("61m"));
+        assert_eq!(Heatmap::default().peak(), None);
+
+        let csv = heatmap.to_csv();
+        assert_eq!(csv.lines().count(), 8);
+        assert!(csv.lines().nth(3).unwrap().starts_with("Wed,0,0,0,0,0,0,0,0,0,3600000,60000,0"));
+    }
+}
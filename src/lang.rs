@@ -0,0 +1,225 @@
+use crate::content::Content;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Metadata key holding the detected language code
+pub const LANG_KEY: &str = "lang";
+/// Metadata key holding the detection confidence (0.00 to 1.00)
+pub const CONFIDENCE_KEY: &str = "lang_confidence";
+
+/// Sample text each language profile is built from
+const SAMPLES: &[(&str, &str)] = &[
+    ("en", "The government announced on Monday that the new policy will take effect next year. \
+        Many people have said that they are worried about what this means for their families and \
+        their work. It is not clear how the plan would be paid for, but officials say they have \
+        been working with the city to find the money. We will have more on this story as it \
+        develops, and you can read the full report on our website. This is one of the most \
+        important decisions that the council has made in years, and there is still a lot of \
+        work to do before it is ready."),
+    ("de", "Die Regierung hat am Montag angekündigt, dass die neue Regelung im nächsten Jahr in \
+        Kraft treten wird. Viele Menschen haben gesagt, dass sie sich Sorgen darüber machen, was \
+        das für ihre Familien und ihre Arbeit bedeutet. Es ist nicht klar, wie der Plan bezahlt \
+        werden soll, aber die Behörden sagen, dass sie mit der Stadt zusammenarbeiten, um das \
+        Geld zu finden. Wir werden mehr über diese Geschichte berichten, sobald sie sich \
+        entwickelt, und den ganzen Bericht können Sie auf unserer Webseite lesen. Das ist eine \
+        der wichtigsten Entscheidungen, die der Rat seit Jahren getroffen hat."),
+    ("fr", "Le gouvernement a annoncé lundi que la nouvelle politique entrera en vigueur l'année \
+        prochaine. Beaucoup de gens ont dit qu'ils étaient inquiets de ce que cela signifie pour \
+        leurs familles et leur travail. On ne sait pas comment le plan sera financé, mais les \
+        responsables disent qu'ils travaillent avec la ville pour trouver l'argent. Nous aurons \
+        plus d'informations sur cette histoire au fur et à mesure, et vous pouvez lire le rapport \
+        complet sur notre site. C'est une des décisions les plus importantes que le conseil a \
+        prises depuis des années."),
+    ("es", "El gobierno anunció el lunes que la nueva política entrará en vigor el próximo año. \
+        Muchas personas han dicho que están preocupadas por lo que esto significa para sus \
+        familias y su trabajo. No está claro cómo se pagaría el plan, pero los funcionarios dicen \
+        que han estado trabajando con la ciudad para encontrar el dinero. Tendremos más sobre \
+        esta historia a medida que se desarrolle, y usted puede leer el informe completo en \
+        nuestro sitio. Esta es una de las decisiones más importantes que el consejo ha tomado en \
+        años."),
+    ("it", "Il governo ha annunciato lunedì che la nuova politica entrerà in vigore il prossimo \
+        anno. Molte persone hanno detto di essere preoccupate per quello che questo significa per \
+        le loro famiglie e il loro lavoro. Non è chiaro come verrebbe pagato il piano, ma i \
+        funzionari dicono di aver lavorato con la città per trovare i soldi. Avremo altre notizie \
+        su questa storia man mano che si sviluppa, e potete leggere il rapporto completo sul \
+        nostro sito. Questa è una delle decisioni più importanti che il consiglio ha preso negli \
+        ultimi anni."),
+    ("pt", "O governo anunciou na segunda-feira que a nova política entrará em vigor no próximo \
+        ano. Muitas pessoas disseram que estão preocupadas com o que isso significa para as suas \
+        famílias e o seu trabalho. Não está claro como o plano seria pago, mas as autoridades \
+        dizem que têm trabalhado com a cidade para encontrar o dinheiro. Teremos mais sobre esta \
+        história à medida que ela se desenvolve, e você pode ler o relatório completo no nosso \
+        site. Esta é uma das decisões mais importantes que o conselho tomou em anos."),
+    ("nl", "De regering heeft maandag aangekondigd dat het nieuwe beleid volgend jaar van kracht \
+        wordt. Veel mensen hebben gezegd dat ze zich zorgen maken over wat dit betekent voor hun \
+        gezinnen en hun werk. Het is niet duidelijk hoe het plan betaald zou worden, maar \
+        ambtenaren zeggen dat ze met de stad samenwerken om het geld te vinden. We zullen meer \
+        over dit verhaal hebben zodra het zich ontwikkelt, en u kunt het volledige rapport op \
+        onze website lezen. Dit is een van de belangrijkste beslissingen die de raad in jaren \
+        heeft genomen."),
+    ("ru", "Правительство объявило в понедельник, что новая политика вступит в силу в следующем \
+        году. Многие люди говорят, что они беспокоятся о том, что это значит для их семей и их \
+        работы. Не ясно, как будет оплачен этот план, но чиновники говорят, что они работают с \
+        городом, чтобы найти деньги. Мы расскажем больше об этой истории по мере её развития, и \
+        вы можете прочитать полный отчёт на нашем сайте. Это одно из самых важных решений, \
+        которые совет принял за последние годы."),
+];
+
+/// Detected language with a confidence between 0.0 and 1.0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// ISO 639-1 language code
+    pub lang: &'static str,
+    /// How clearly the best language beat the runner-up
+    pub confidence: f64,
+}
+
+/// Normalized character trigram frequencies
+type Profile = HashMap<String, f64>;
+
+fn profile(text: &str) -> Profile {
+    let mut counts: HashMap<String, f64> = HashMap::new();
+    // Links say nothing about the language around them
+    let cleaned: String = text
+        .split_whitespace()
+        .filter(|word| crate::links::extract(word).is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .map(|c| if c.is_alphabetic() || c == '\'' { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase();
+
+    for word in cleaned.split_whitespace() {
+        // Pad words so trigrams capture word starts and endings
+        let padded: Vec<char> = format!(" {} ", word).chars().collect();
+        for gram in padded.windows(3) {
+            *counts.entry(gram.iter().collect()).or_default() += 1.0;
+        }
+    }
+
+    let norm = counts.values().map(|v| v * v).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        for value in counts.values_mut() {
+            *value /= norm;
+        }
+    }
+    counts
+}
+
+fn profiles() -> &'static [(&'static str, Profile)] {
+    static PROFILES: OnceLock<Vec<(&'static str, Profile)>> = OnceLock::new();
+    PROFILES.get_or_init(|| SAMPLES.iter().map(|(lang, sample)| (*lang, profile(sample))).collect())
+}
+
+/// Languages that can be detected
+pub fn supported_languages() -> impl Iterator<Item = &'static str> {
+    SAMPLES.iter().map(|(lang, _)| *lang)
+}
+
+/// Identify the language of a text from its character trigrams
+///
+/// Returns `None` for text without letters or without any trigram in common with a
+/// known language. Short texts get low confidence.
+pub fn detect(text: &str) -> Option<Detection> {
+    let input = profile(text);
+    if input.is_empty() {
+        return None;
+    }
+
+    let mut scores: Vec<(&'static str, f64)> = profiles()
+        .iter()
+        .map(|(lang, reference)| {
+            let similarity = input
+                .iter()
+                .filter_map(|(gram, weight)| reference.get(gram).map(|r| r * weight))
+                .sum::<f64>();
+            (*lang, similarity)
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let (lang, best) = scores[0];
+    if best <= 0.0 {
+        return None;
+    }
+    let runner_up = scores.get(1).map_or(0.0, |(_, score)| *score);
+    Some(Detection {
+        lang,
+        confidence: 1.0 - runner_up / best,
+    })
+}
+
+/// Set the `lang` and `lang_confidence` metadata keys, unless the source already set `lang`
+pub fn annotate(content: &mut Content) {
+    if content.metadata.contains_key(LANG_KEY) {
+        return;
+    }
+    if let Some(detection) = detect(&content.text) {
+        content.metadata.insert(LANG_KEY.to_string(), detection.lang.to_string());
+        content
+            .metadata
+            .insert(CONFIDENCE_KEY.to_string(), format!("{:.2}", detection.confidence));
+    }
+}
+
+/// Language and confidence of content: from its metadata (confidence 1.0 if the source
+/// gave none), otherwise detected from its text
+pub fn language_of(content: &Content) -> Option<(String, f64)> {
+    match content.metadata.get(LANG_KEY) {
+        Some(lang) => {
+            let confidence = content
+                .metadata
+                .get(CONFIDENCE_KEY)
+                .and_then(|c| c.parse().ok())
+                .unwrap_or(1.0);
+            Some((lang.clone(), confidence))
+        }
+        None => detect(&content.text).map(|d| (d.lang.to_string(), d.confidence)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let cases = [
+            ("en", "Scientists say the storm could bring heavy rain to the coast later this week"),
+            ("de", "Die Wissenschaftler sagen, dass der Sturm später in dieser Woche starken Regen bringen könnte"),
+            ("fr", "Les scientifiques disent que la tempête pourrait apporter de fortes pluies sur la côte cette semaine"),
+            ("es", "Los científicos dicen que la tormenta podría traer lluvias fuertes a la costa esta semana"),
+            ("it", "Gli scienziati dicono che la tempesta potrebbe portare forti piogge sulla costa questa settimana"),
+            ("pt", "Os cientistas dizem que a tempestade pode trazer chuvas fortes para a costa esta semana"),
+            ("nl", "Wetenschappers zeggen dat de storm later deze week zware regen naar de kust kan brengen"),
+            ("ru", "Учёные говорят, что шторм может принести сильные дожди на побережье на этой неделе"),
+        ];
+        for (lang, text) in cases {
+            let detection = detect(text).unwrap();
+            assert_eq!(detection.lang, lang, "{}", text);
+            assert!(detection.confidence > 0.0);
+        }
+
+        assert_eq!(detect("1234 !!"), None);
+    }
+
+    #[test]
+    fn test_annotate_keeps_source_language() {
+        let mut content = Content {
+            id: "test".to_string(),
+            text: "The weather is nice today and we are going to the park".to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+        annotate(&mut content);
+        assert_eq!(content.metadata[LANG_KEY], "en");
+
+        content.metadata.insert(LANG_KEY.to_string(), "de".to_string());
+        content.metadata.remove(CONFIDENCE_KEY);
+        annotate(&mut content);
+        assert_eq!(language_of(&content), Some(("de".to_string(), 1.0)));
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Built-in clickbait and outrage lexicon, in the same format as user lexicon files
+pub const BUILTIN_LEXICON: &str = r#"
+[[category]]
+name = "forward-reference"
+weight = 0.4
+phrases = [
+    "you won't believe", "you will never believe", "what happened next", "what happens next",
+    "this is why", "here's why", "the reason why", "will shock you", "will blow your mind",
+    "one weird trick", "this one trick", "nobody is talking about", "you need to know",
+    "before it's too late", "wait until you see", "you'll never guess",
+]
+
+[[category]]
+name = "listicle"
+weight = 0.3
+patterns = [
+    '\b\d+\s+(things|reasons|ways|facts|signs|tips|tricks|secrets|photos|times|mistakes|celebrities)\b',
+]
+
+[[category]]
+name = "superlative"
+weight = 0.2
+phrases = [
+    "best ever", "worst ever", "of all time", "most shocking", "incredible", "unbelievable",
+    "insane", "ultimate", "jaw-dropping", "mind-blowing", "epic", "genius",
+]
+
+[[category]]
+name = "outrage"
+weight = 0.3
+phrases = [
+    "outrage", "outraged", "slams", "destroys", "furious", "fury", "disgusting", "shameful",
+    "backlash", "meltdown", "blasts", "rips into", "humiliates", "sparks anger", "under fire",
+]
+"#;
+
+/// Group of terms that contribute the same weight to the score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Category {
+    /// Category name shown in explanations
+    pub name: String,
+    /// Score added for each distinct matching term
+    pub weight: f64,
+    /// Case-insensitive phrases matched on word boundaries
+    #[serde(default)]
+    pub phrases: Vec<String>,
+    /// Case-insensitive regular expressions
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct LexiconFile {
+    category: Vec<Category>,
+}
+
+/// A term that matched, with its contribution to the score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LexiconHit {
+    /// Category of the term
+    pub category: String,
+    /// Phrase or pattern that matched
+    pub term: String,
+    /// Text that matched it
+    pub matched: String,
+    /// Score contribution
+    pub weight: f64,
+}
+
+/// Score of a text between 0.0 and 1.0, with the terms that produced it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LexiconScore {
+    /// Sum of the hit weights, capped at 1.0
+    pub score: f64,
+    /// Matching terms in lexicon order
+    pub hits: Vec<LexiconHit>,
+}
+
+impl fmt::Display for LexiconScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "score {:.2}", self.score)?;
+        for hit in &self.hits {
+            write!(f, "\n  +{:.2} {}: \"{}\"", hit.weight, hit.category, hit.matched)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compiled clickbait/outrage lexicon
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+    categories: Vec<(Category, Vec<(String, Regex)>)>,
+}
+
+impl Lexicon {
+    /// Compile a lexicon from categories
+    pub fn new(categories: Vec<Category>) -> Result<Self> {
+        let categories = categories
+            .into_iter()
+            .map(|category| {
+                let phrases = category
+                    .phrases
+                    .iter()
+                    .map(|phrase| (phrase.clone(), format!(r"(?i)\b{}\b", regex::escape(phrase))));
+                let patterns = category
+                    .patterns
+                    .iter()
+                    .map(|pattern| (pattern.clone(), format!("(?i){}", pattern)));
+                let terms = phrases
+                    .chain(patterns)
+                    .map(|(term, pattern)| {
+                        let regex = Regex::new(&pattern)
+                            .with_context(|| format!("Invalid term '{}' in category '{}'", term, category.name))?;
+                        Ok((term, regex))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((category, terms))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { categories })
+    }
+
+    /// The built-in lexicon
+    pub fn builtin() -> Self {
+        // Compiled once; clones share the compiled regexes
+        static BUILTIN: OnceLock<Lexicon> = OnceLock::new();
+        BUILTIN
+            .get_or_init(|| Self::from_toml(BUILTIN_LEXICON).expect("built-in lexicon is valid"))
+            .clone()
+    }
+
+    /// Parse a lexicon file: a TOML list of `[[category]]` tables
+    pub fn from_toml(text: &str) -> Result<Self> {
+        Self::new(toml::from_str::<LexiconFile>(text)?.category)
+    }
+
+    /// Load a lexicon file
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("Invalid lexicon {}", path.display()))
+    }
+
+    /// Lexicon categories
+    pub fn categories(&self) -> impl Iterator<Item = &Category> {
+        self.categories.iter().map(|(category, _)| category)
+    }
+
+    /// Score a text, counting each matching term once
+    pub fn score(&self, text: &str) -> LexiconScore {
+        let mut hits = Vec::new();
+        for (category, terms) in &self.categories {
+            for (term, regex) in terms {
+                if let Some(found) = regex.find(text) {
+                    hits.push(LexiconHit {
+                        category: category.name.clone(),
+                        term: term.clone(),
+                        matched: found.as_str().to_string(),
+                        weight: category.weight,
+                    });
+                }
+            }
+        }
+
+        // Summing no hits gives -0.0, which would print as "-0.00"
+        let score = hits.iter().fold(0.0, |sum, hit| sum + hit.weight).clamp(0.0, 1.0);
+        LexiconScore { score, hits }
+    }
+}
+
+impl Default for Lexicon {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_scores() {
+        let lexicon = Lexicon::builtin();
+
+        let bait = lexicon.score("17 Reasons You Won't Believe What Happened Next");
+        assert!(bait.score >= 0.9, "{}", bait);
+        let categories: Vec<_> = bait.hits.iter().map(|hit| hit.category.as_str()).collect();
+        assert_eq!(categories, vec!["forward-reference", "forward-reference", "listicle"]);
+        assert_eq!(bait.hits[2].matched, "17 Reasons");
+
+        let outrage = lexicon.score("Senator slams disgusting proposal");
+        assert!((outrage.score - 0.6).abs() < 1e-9);
+
+        let plain = lexicon.score("Council approves road budget");
+        assert_eq!(plain.score, 0.0);
+        assert!(plain.score.is_sign_positive());
+        // Phrases match whole words only
+        assert_eq!(lexicon.score("Epicurus on pleasure").score, 0.0);
+    }
+
+    #[test]
+    fn test_user_lexicon() {
+        let lexicon = Lexicon::from_toml(
+            r#"
+            [[category]]
+            name = "crypto-hype"
+            weight = 0.5
+            phrases = ["to the moon"]
+            patterns = ['\d+x gains']
+            "#,
+        )
+        .unwrap();
+
+        let score = lexicon.score("Token going TO THE MOON, 100x gains");
+        assert_eq!(score.score, 1.0);
+        assert_eq!(score.to_string(), "score 1.00\n  +0.50 crypto-hype: \"TO THE MOON\"\n  +0.50 crypto-hype: \"100x gains\"");
+
+        assert!(Lexicon::from_toml("[[category]]\nname = \"x\"\nweight = 1\npatterns = ['(']").is_err());
+    }
+}
@@ -1,10 +1,29 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use sqlx::SqlitePool;
 
+pub mod allowlist;
 pub mod attention;
+pub mod blocklist;
 pub mod content;
+pub mod dedup;
+pub mod dsl;
+pub mod features;
+pub mod fixture;
+pub mod heatmap;
+pub mod lang;
+pub mod lexicon;
+pub mod links;
+pub mod lint;
+pub mod reputation;
+pub mod rulefile;
+pub mod schedule;
+pub mod sentiment;
 pub mod store;
+pub mod summarize;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod federation;
 
 /// Core processor for the Sovereign Attention Protocol
@@ -12,6 +31,8 @@ pub struct LocalProcessor {
     attention_tracker: Arc<Mutex<attention::AttentionTracker>>,
     content_filter: Arc<Mutex<content::ContentFilter>>,
     data_store: Arc<store::DataStore>,
+    active_profile: Arc<Mutex<Option<String>>>,
+    plugins: Arc<content::PluginRegistry>,
 }
 
 impl LocalProcessor {
@@ -23,30 +44,123 @@ impl LocalProcessor {
         // Initialize database schema
         data_store.initialize().await?;
 
-        Ok(Self {
+        let plugins = Arc::new(content::PluginRegistry::new());
+        let processor = Self {
             attention_tracker: Arc::new(Mutex::new(attention::AttentionTracker::new())),
-            content_filter: Arc::new(Mutex::new(content::ContentFilter::new())),
+            content_filter: Arc::new(Mutex::new(content::ContentFilter::with_plugins(plugins.clone()))),
             data_store: data_store,
-        })
+            active_profile: Arc::new(Mutex::new(None)),
+            plugins,
+        };
+
+        // Restore the active profile and its rules from the last session
+        let active = processor.data_store.get_active_profile().await?;
+        processor.load_rules(active).await?;
+
+        let seen = processor.data_store.get_fingerprints().await?;
+        processor.content_filter.lock().await.load_seen(seen);
+
+        let allowlist = processor.data_store.get_allowlist().await?;
+        processor.content_filter.lock().await.set_allowlist(allowlist);
+
+        let reputations = processor.data_store.get_reputations().await?;
+        processor.content_filter.lock().await.load_reputations(reputations);
+
+        // Hits older than the longest rate-limit window can no longer affect a decision
+        let longest_window = processor
+            .data_store
+            .get_all_rules()
+            .await?
+            .iter()
+            .filter_map(|rule| match rule.action {
+                content::ActionType::RateLimit { window_hours, .. } => Some(window_hours),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let since = chrono::Utc::now() - chrono::Duration::hours(longest_window);
+        processor.data_store.prune_rate_limit_hits(since).await?;
+        let hits = processor.data_store.get_rate_limit_hits(since).await?;
+        processor.content_filter.lock().await.load_rate_limit_hits(hits);
+
+        Ok(processor)
+    }
+
+    /// Get the stored rules for a profile, or all rules for `None`
+    async fn rule_set(&self, profile: Option<&str>) -> anyhow::Result<Vec<content::Rule>> {
+        match profile {
+            Some(name) => self.data_store.get_profile_rules(name).await,
+            None => self.data_store.get_all_rules().await,
+        }
+    }
+
+    /// Load the rule set for a profile (or all rules for `None`) into the filter
+    ///
+    /// Invalid stored rules are skipped with a warning; `sap lint-rules` reports them.
+    async fn load_rules(&self, profile: Option<String>) -> anyhow::Result<()> {
+        let rules = self
+            .rule_set(profile.as_deref())
+            .await?
+            .into_iter()
+            .filter(|rule| match rule.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Skipping invalid rule {}: {}", rule.id, e);
+                    false
+                }
+            })
+            .collect();
+
+        self.content_filter.lock().await.replace_rules(rules)?;
+        *self.active_profile.lock().await = profile;
+        Ok(())
     }
 
     /// Process content through filters and track attention
     pub async fn process_content(&self, content: content::Content) -> anyhow::Result<Option<content::Content>> {
+        let mut content = content;
+        Self::annotate(&mut content);
+
         // Apply content filtering
-        let filtered = {
-            let filter = self.content_filter.lock().await;
-            filter.process_content(&content).await?
-        };
+        let now = chrono::Utc::now();
+        // The filter stays locked until any rate-limit hit is counted, so concurrent items
+        // can't overrun the limit
+        let evaluation = self.content_filter.lock().await.process_at(&content, now).await?;
+        self.record_rule_stats(&content, &evaluation).await?;
+        self.record_reputation(&content, &evaluation, now).await?;
+        if let (true, Some(rule_id)) = (evaluation.counted, &evaluation.matched) {
+            self.data_store.record_rate_limit_hit(rule_id, now).await?;
+        }
+        if let Some(item) = &evaluation.queued {
+            self.data_store.queue_content(item).await?;
+        }
+        let filtered = evaluation.result;
 
         // If content wasn't filtered out, track attention
         if let Some(processed) = filtered {
+            // Remember what was shown so reposts can be detected
+            let seen = self.content_filter.lock().await.record_seen(&content, now);
+            if let Some(seen) = seen {
+                self.data_store.record_fingerprint(&seen).await?;
+            }
+
             let mut tracker = self.attention_tracker.lock().await;
-            tracker.track_focus(&processed.id, processed.view_duration);
+            let event = tracker.track_focus(&processed.id, processed.view_duration);
             
             // Persist metrics
             if let Some(metrics) = tracker.get_focus_metrics(&processed.id) {
                 self.data_store.save_metrics(&processed.id, metrics).await?;
             }
+            // Items scrolled past without any attention don't make up a session
+            if event.duration > 0 {
+                self.data_store.record_attention_event(&event).await?;
+            }
+
+            if let Some(profile) = self.active_profile.lock().await.as_deref() {
+                self.data_store
+                    .record_profile_attention(profile, &processed.id, processed.view_duration)
+                    .await?;
+            }
 
             Ok(Some(processed))
         } else {
@@ -54,10 +168,103 @@ impl LocalProcessor {
         }
     }
 
+    /// Tag links, language and tone so rules and later analysis can use them
+    fn annotate(content: &mut content::Content) {
+        links::annotate(content);
+        lang::annotate(content);
+        sentiment::annotate(content);
+    }
+
+    /// Evaluate content against the rules without recording anything, to explain the outcome
+    pub async fn explain(&self, content: content::Content) -> anyhow::Result<content::Evaluation> {
+        let mut content = content;
+        Self::annotate(&mut content);
+        self.content_filter.lock().await.evaluate(&content).await
+    }
+
+    /// Evaluate a batch of items and return the kept ones ordered by score, highest first
+    ///
+    /// Ranking doesn't count as reading: nothing is recorded and rate limits aren't used
+    /// up. Items with equal scores keep their input order.
+    pub async fn rank(&self, items: Vec<content::Content>) -> anyhow::Result<Vec<content::Content>> {
+        let filter = self.content_filter.lock().await;
+        let mut ranked = Vec::with_capacity(items.len());
+        for mut item in items {
+            Self::annotate(&mut item);
+            if let Some(kept) = filter.evaluate(&item).await?.result {
+                ranked.push(kept);
+            }
+        }
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(ranked)
+    }
+
+    /// Add a processed item's evidence to the reputation of its author and source
+    async fn record_reputation(
+        &self,
+        original: &content::Content,
+        evaluation: &content::Evaluation,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()> {
+        let matched = match &evaluation.matched {
+            Some(rule_id) => self.content_filter.lock().await.get_rule(rule_id),
+            None => None,
+        };
+        let update = reputation::ReputationUpdate::from_evaluation(original, evaluation, matched.as_ref());
+        for (key, value) in reputation::sources_of(original) {
+            let updated = self.data_store.record_reputation(key, value, &original.id, &update, at).await?;
+            if let Some(updated) = updated {
+                self.content_filter.lock().await.set_reputation(&updated);
+            }
+        }
+        Ok(())
+    }
+
+    /// Persist per-rule hit statistics for one processed item
+    async fn record_rule_stats(&self, original: &content::Content, evaluation: &content::Evaluation) -> anyhow::Result<()> {
+        self.data_store.record_rule_evaluations(&evaluation.evaluated).await?;
+        for rule_id in &evaluation.scored {
+            self.data_store.record_rule_match(rule_id, false, false, false, 0).await?;
+        }
+        for rule_id in &evaluation.cleaned {
+            self.data_store.record_rule_match(rule_id, false, false, true, 0).await?;
+        }
+
+        let Some(rule_id) = &evaluation.matched else {
+            return Ok(());
+        };
+        let (filtered, flagged, modified) = match &evaluation.result {
+            None => (true, false, false),
+            Some(processed) => (
+                false,
+                processed.flags.len() > original.flags.len(),
+                processed.text != original.text,
+            ),
+        };
+
+        // Filtered items would have cost their own view time, or an average item's if unknown;
+        // queued items are only delayed
+        let time_saved = if !filtered || evaluation.queued.is_some() {
+            0
+        } else if original.view_duration > 0 {
+            original.view_duration
+        } else {
+            self.data_store.get_average_duration().await?.unwrap_or(0.0).round() as i64
+        };
+
+        self.data_store
+            .record_rule_match(rule_id, filtered, flagged, modified, time_saved)
+            .await
+    }
+
     /// Add a new content filtering rule
+    ///
+    /// While a profile is active the rule only takes effect once it is added to that profile.
     pub async fn add_rule(&self, rule: content::Rule) -> anyhow::Result<()> {
+        rule.validate()?;
+
         // Add rule to filter
-        {
+        if self.active_profile.lock().await.is_none() {
             let mut filter = self.content_filter.lock().await;
             filter.add_rule(rule.clone())?;
         }
@@ -77,13 +284,335 @@ impl LocalProcessor {
         self.data_store.get_all_metrics().await
     }
 
+    /// Split stored attention since `since` into sessions separated by `idle_gap`
+    pub async fn get_session_report(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        idle_gap: chrono::Duration,
+    ) -> anyhow::Result<attention::SessionReport> {
+        let events = self.data_store.get_attention_events(since).await?;
+        Ok(attention::SessionReport::new(&events, idle_gap))
+    }
+
+    /// Attention since `since` by day of the week and hour, in the time at `offset`
+    pub async fn get_heatmap(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        offset: Option<chrono::FixedOffset>,
+    ) -> anyhow::Result<heatmap::Heatmap> {
+        let events = self.data_store.get_attention_events(since).await?;
+        Ok(heatmap::Heatmap::from_events(&events, offset))
+    }
+
     /// Get all active rules
     pub async fn get_rules(&self) -> anyhow::Result<Vec<content::Rule>> {
         self.data_store.get_all_rules().await
     }
 
+    /// Switch the active rule set to a profile, or back to all rules with `None`
+    pub async fn use_profile(&self, profile: Option<&str>) -> anyhow::Result<()> {
+        if let Some(name) = profile {
+            if !self.data_store.profile_exists(name).await? {
+                anyhow::bail!("Profile '{}' does not exist", name);
+            }
+        }
+
+        self.load_rules(profile.map(str::to_string)).await?;
+        self.data_store.set_active_profile(profile).await
+    }
+
+    /// Get the name of the active profile, if any
+    pub async fn active_profile(&self) -> Option<String> {
+        self.active_profile.lock().await.clone()
+    }
+
+    /// Create a new, empty profile
+    pub async fn create_profile(&self, name: &str) -> anyhow::Result<()> {
+        self.data_store.create_profile(name).await
+    }
+
+    /// Delete a profile, falling back to all rules if it was active
+    pub async fn delete_profile(&self, name: &str) -> anyhow::Result<()> {
+        if self.active_profile().await.as_deref() == Some(name) {
+            self.use_profile(None).await?;
+        }
+        self.data_store.delete_profile(name).await
+    }
+
+    /// Add an existing rule to a profile
+    pub async fn add_rule_to_profile(&self, profile: &str, rule_id: &str) -> anyhow::Result<()> {
+        if !self.data_store.profile_exists(profile).await? {
+            anyhow::bail!("Profile '{}' does not exist", profile);
+        }
+        if self.data_store.get_rule(rule_id).await?.is_none() {
+            anyhow::bail!("Rule '{}' does not exist", rule_id);
+        }
+
+        self.data_store.add_rule_to_profile(profile, rule_id).await?;
+        self.reload_if_active(profile).await
+    }
+
+    /// Remove a rule from a profile
+    pub async fn remove_rule_from_profile(&self, profile: &str, rule_id: &str) -> anyhow::Result<()> {
+        self.data_store.remove_rule_from_profile(profile, rule_id).await?;
+        self.reload_if_active(profile).await
+    }
+
+    async fn reload_if_active(&self, profile: &str) -> anyhow::Result<()> {
+        if self.active_profile().await.as_deref() == Some(profile) {
+            self.load_rules(Some(profile.to_string())).await?;
+        }
+        Ok(())
+    }
+
+    /// Run a fixture suite against the active rules, without tracking attention
+    pub async fn test_rules(&self, suite: &fixture::TestSuite) -> anyhow::Result<Vec<fixture::CaseResult>> {
+        let filter = self.content_filter.lock().await;
+        fixture::run_suite(&filter, suite).await
+    }
+
+    /// Lint the stored rules of the active profile (or all rules)
+    ///
+    /// `models` holds the IDs of installed ML models.
+    pub async fn lint_rules(&self, models: &HashSet<String>) -> anyhow::Result<Vec<lint::LintIssue>> {
+        let active = self.active_profile().await;
+        let rules = self.rule_set(active.as_deref()).await?;
+        Ok(lint::lint_rules(&rules, models, &self.plugins))
+    }
+
+    /// Registry of condition and action plugins available to rules
+    pub fn plugins(&self) -> &content::PluginRegistry {
+        &self.plugins
+    }
+
+    /// Register every `.wasm` module in `dir` as a condition plugin named by its file stem
+    #[cfg(feature = "wasm")]
+    pub fn load_wasm_plugins(&self, dir: &std::path::Path, limits: wasm::WasmLimits) -> anyhow::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for (name, plugin) in wasm::load_dir(dir, limits)? {
+            self.plugins.register_condition(name.clone(), plugin);
+            names.push(name);
+        }
+        Ok(names)
+    }
+
+    /// Import a keyword/domain blocklist as rules owned by `list_id`
+    ///
+    /// Entries whose condition already exists in a rule outside the list are skipped.
+    /// Re-importing the same `list_id` updates its rules in place. The import is refused
+    /// if a generated rule ID would replace a rule the list does not own.
+    pub async fn import_list(
+        &self,
+        list_id: &str,
+        source: &str,
+        format: blocklist::ListFormat,
+        input: &str,
+        action: content::ActionType,
+    ) -> anyhow::Result<blocklist::ImportSummary> {
+        let parsed = blocklist::parse_list(input, format);
+
+        let owned: HashSet<String> = self.data_store.get_blocklist_rule_ids(list_id).await?.into_iter().collect();
+        let others: Vec<content::Rule> = self
+            .data_store
+            .get_all_rules()
+            .await?
+            .into_iter()
+            .filter(|rule| !owned.contains(&rule.id))
+            .collect();
+        let existing: HashSet<String> = others.iter().map(|rule| rule.condition.key()).collect();
+
+        let total = parsed.conditions.len();
+        let conditions: Vec<_> = parsed
+            .conditions
+            .into_iter()
+            .filter(|condition| !existing.contains(&condition.key()))
+            .collect();
+        let duplicates = total - conditions.len();
+
+        let rules = blocklist::rules_for_list(list_id, conditions, &action);
+        let other_ids: HashSet<&str> = others.iter().map(|rule| rule.id.as_str()).collect();
+        if let Some(rule) = rules.iter().find(|rule| other_ids.contains(rule.id.as_str())) {
+            anyhow::bail!(
+                "List '{}' would replace rule '{}', which it does not own; import it under another ID",
+                list_id,
+                rule.id
+            );
+        }
+        let removed = self.data_store.replace_blocklist(list_id, source, format, &rules).await?;
+
+        let active = self.active_profile().await;
+        self.load_rules(active).await?;
+
+        Ok(blocklist::ImportSummary {
+            imported: rules.len(),
+            duplicates,
+            removed,
+            skipped: parsed.skipped,
+        })
+    }
+
+    /// Load a rules file, sync it into the stored rules and reload the active rule set
+    ///
+    /// The whole file is validated first; if it is invalid, neither the store nor the
+    /// active rules change.
+    pub async fn sync_rule_file(&self, file: &mut rulefile::RuleFile) -> anyhow::Result<rulefile::SyncSummary> {
+        let rules = file.load().await?;
+        let path = file.path().display().to_string();
+        let removed = self.data_store.replace_rule_file(&path, &rules).await?;
+
+        let active = self.active_profile().await;
+        self.load_rules(active).await?;
+
+        Ok(rulefile::SyncSummary {
+            loaded: rules.len(),
+            removed,
+        })
+    }
+
+    /// Replace the built-in clickbait lexicon
+    pub async fn set_lexicon(&self, lexicon: lexicon::Lexicon) {
+        self.content_filter.lock().await.set_lexicon(lexicon);
+    }
+
+    /// Replace the tracking parameters link-cleaning rules remove
+    pub async fn set_tracking_params(&self, params: Vec<String>) {
+        self.content_filter.lock().await.set_tracking_params(params);
+    }
+
+    /// Clickbait score of a text, explaining which terms produced it
+    pub async fn score_clickbait(&self, text: &str) -> lexicon::LexiconScore {
+        self.content_filter.lock().await.score_clickbait(text)
+    }
+
+    /// Let content matching `entry` through before any rule is evaluated
+    pub async fn add_allow_entry(&self, entry: allowlist::AllowEntry) -> anyhow::Result<()> {
+        self.data_store.add_allow_entry(&entry).await?;
+        self.reload_allowlist().await
+    }
+
+    /// Remove an allowlist entry, returning whether it existed
+    pub async fn remove_allow_entry(&self, entry: &allowlist::AllowEntry) -> anyhow::Result<bool> {
+        let removed = self.data_store.remove_allow_entry(entry).await?;
+        self.reload_allowlist().await?;
+        Ok(removed)
+    }
+
+    /// Get all allowlist entries
+    pub async fn get_allowlist(&self) -> anyhow::Result<Vec<allowlist::AllowEntry>> {
+        self.data_store.get_allowlist().await
+    }
+
+    async fn reload_allowlist(&self) -> anyhow::Result<()> {
+        let entries = self.data_store.get_allowlist().await?;
+        self.content_filter.lock().await.set_allowlist(entries);
+        Ok(())
+    }
+
+    /// Get all reputations, highest score first
+    pub async fn get_reputations(&self) -> anyhow::Result<Vec<reputation::Reputation>> {
+        let mut reputations = self.data_store.get_reputations().await?;
+        reputations.sort_by(|a, b| b.score().total_cmp(&a.score()));
+        Ok(reputations)
+    }
+
+    /// Set a source's reputation score by hand, or return it to the computed score with `None`
+    pub async fn set_reputation(&self, key: &str, value: &str, score: Option<f64>) -> anyhow::Result<()> {
+        if let Some(score) = score {
+            anyhow::ensure!((0.0..=1.0).contains(&score), "Reputation score must be between 0.0 and 1.0");
+        }
+        self.data_store.set_reputation(key, value, score).await?;
+        if let Some(updated) = self.data_store.get_reputation(key, value).await? {
+            self.content_filter.lock().await.set_reputation(&updated);
+        }
+        Ok(())
+    }
+
+    /// Forget the reputation of one source, or of every source under `key`
+    pub async fn reset_reputation(&self, key: &str, value: Option<&str>) -> anyhow::Result<u64> {
+        let removed = self.data_store.reset_reputation(key, value).await?;
+        let reputations = self.data_store.get_reputations().await?;
+        self.content_filter.lock().await.load_reputations(reputations);
+        Ok(removed)
+    }
+
+    /// Get queued content that is due for the digest, or all queued content with `include_pending`
+    pub async fn get_digest(&self, include_pending: bool) -> anyhow::Result<Vec<content::QueuedItem>> {
+        let due = if include_pending { None } else { Some(chrono::Utc::now()) };
+        self.data_store.get_queue(due).await
+    }
+
+    /// Remove items from the digest queue once they have been read
+    pub async fn release_queued(&self, content_ids: &[String]) -> anyhow::Result<()> {
+        self.data_store.release_queued(content_ids).await
+    }
+
+    /// Get all imported blocklists
+    pub async fn get_blocklists(&self) -> anyhow::Result<Vec<blocklist::Blocklist>> {
+        self.data_store.get_blocklists().await
+    }
+
+    /// Get all profiles
+    pub async fn get_profiles(&self) -> anyhow::Result<Vec<content::Profile>> {
+        self.data_store.get_profiles().await
+    }
+
+    /// Get attention metrics aggregated per profile
+    pub async fn get_profile_metrics(&self) -> anyhow::Result<Vec<attention::ProfileMetrics>> {
+        self.data_store.get_profile_metrics().await
+    }
+
+    /// Get hit statistics for all rules
+    pub async fn get_rule_stats(&self) -> anyhow::Result<Vec<content::RuleStats>> {
+        self.data_store.get_rule_stats().await
+    }
+
     /// Clean up old metrics data
     pub async fn cleanup(&self, days_to_keep: i64) -> anyhow::Result<()> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days_to_keep);
+        self.content_filter.lock().await.prune_seen(cutoff);
         self.data_store.cleanup(days_to_keep).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_explain_leaves_rate_limits_alone() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let database_url = format!("sqlite:{}", dir.path().join("test.db").display());
+        store::DataStore::create_database(&database_url).await?;
+        let processor = LocalProcessor::new(&database_url).await?;
+        processor
+            .add_rule(content::Rule {
+                id: "once".to_string(),
+                condition: content::ConditionType::Keyword("crypto".to_string()),
+                action: content::ActionType::RateLimit { max: 1, window_hours: 24 },
+                priority: 0,
+                schedule: None,
+            })
+            .await?;
+
+        let content = content::Content {
+            id: "test".to_string(),
+            text: "crypto news".to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        };
+        for _ in 0..3 {
+            let evaluation = processor.explain(content.clone()).await?;
+            assert!(evaluation.result.is_some());
+        }
+
+        // The one allowed match is still there for real processing
+        assert!(processor.process_content(content.clone()).await?.is_some());
+        assert!(processor.process_content(content.clone()).await?.is_none());
+        assert!(processor.explain(content).await?.result.is_none());
+        Ok(())
+    }
+}
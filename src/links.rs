@@ -0,0 +1,367 @@
+use crate::content::Content;
+use regex::Regex;
+use std::sync::OnceLock;
+use url::{form_urlencoded, Url};
+
+/// Metadata key holding the canonical URLs found in the content, space separated
+pub const URLS_KEY: &str = "urls";
+/// Metadata key holding the distinct hosts of those URLs, space separated
+pub const DOMAINS_KEY: &str = "domains";
+
+/// Query parameters that only identify where a click came from; `*` matches any suffix
+pub const TRACKING_PARAMS: &[&str] = &[
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "twclid", "igshid",
+    "mc_cid", "mc_eid", "_hsenc", "_hsmi", "mkt_tok", "ref_src", "ref_url", "oly_enc_id", "oly_anon_id",
+    "vero_id", "wickedid", "si",
+];
+
+/// Redirect wrappers as `(host, path, parameter holding the target URL)`
+const REDIRECT_WRAPPERS: &[(&str, &str, &str)] = &[
+    ("www.google.com", "/url", "q"),
+    ("www.google.com", "/url", "url"),
+    ("l.facebook.com", "/l.php", "u"),
+    ("lm.facebook.com", "/l.php", "u"),
+    ("l.messenger.com", "/l.php", "u"),
+    ("l.instagram.com", "/", "u"),
+    ("out.reddit.com", "/", "url"),
+    ("www.youtube.com", "/redirect", "q"),
+    ("t.umblr.com", "/redirect", "z"),
+    ("www.linkedin.com", "/redir/redirect", "url"),
+    ("steamcommunity.com", "/linkfilter/", "url"),
+    ("slack-redir.net", "/link", "url"),
+];
+
+/// Wrappers nested deeper than this are left alone
+const MAX_REDIRECT_DEPTH: usize = 3;
+
+/// Mobile hosts and their canonical desktop host
+const MOBILE_HOSTS: &[(&str, &str)] = &[
+    ("m.youtube.com", "www.youtube.com"),
+    ("mobile.twitter.com", "twitter.com"),
+    ("m.facebook.com", "www.facebook.com"),
+    ("mobile.x.com", "x.com"),
+    ("en.m.wikipedia.org", "en.wikipedia.org"),
+    ("old.reddit.com", "www.reddit.com"),
+    ("dx.doi.org", "doi.org"),
+];
+
+fn url_regex() -> &'static Regex {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    URL.get_or_init(|| Regex::new(r#"(?i)\b(?:https?://|www\.)[^\s<>"]+"#).expect("URL pattern is valid"))
+}
+
+/// Byte ranges of the raw URLs in a text, without trailing punctuation
+fn spans(text: &str) -> Vec<std::ops::Range<usize>> {
+    url_regex()
+        .find_iter(text)
+        .map(|m| {
+            let mut url = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', ']', '}']);
+            // Keep a closing parenthesis only if the URL opened one, as in Wikipedia links
+            if url.ends_with(')') && !url.contains('(') {
+                url = url.trim_end_matches(')');
+            }
+            m.start()..m.start() + url.len()
+        })
+        .collect()
+}
+
+/// Raw URLs in a text, without trailing punctuation
+pub fn extract(text: &str) -> Vec<&str> {
+    spans(text).into_iter().map(|span| &text[span]).collect()
+}
+
+fn parse(raw: &str) -> Option<Url> {
+    let url = if raw.len() >= 4 && raw[..4].eq_ignore_ascii_case("www.") {
+        Url::parse(&format!("https://{}", raw)).ok()?
+    } else {
+        Url::parse(raw).ok()?
+    };
+    (matches!(url.scheme(), "http" | "https") && url.host_str().is_some()).then_some(url)
+}
+
+/// Target of a known redirect wrapper such as `https://www.google.com/url?q=<target>`
+fn unwrap_redirect(url: &Url) -> Option<Url> {
+    let host = url.host_str()?.to_lowercase();
+    REDIRECT_WRAPPERS
+        .iter()
+        .filter(|(wrapper, path, _)| *wrapper == host && url.path().starts_with(path))
+        .find_map(|(_, _, param)| {
+            let (_, target) = url.query_pairs().find(|(name, _)| name == param)?;
+            parse(&target)
+        })
+}
+
+fn unwrap_redirects(mut url: Url) -> Url {
+    for _ in 0..MAX_REDIRECT_DEPTH {
+        match unwrap_redirect(&url) {
+            Some(target) => url = target,
+            None => break,
+        }
+    }
+    url
+}
+
+/// A URL without redirect wrappers or the query parameters listed in `params`
+///
+/// Unlike [`canonicalize`] the rest of the URL is left as written. Returns `None` when
+/// there is nothing to remove.
+pub fn clean_url(raw: &str, params: &[impl AsRef<str>]) -> Option<String> {
+    let url = parse(raw)?;
+    let mut cleaned = unwrap_redirects(url.clone());
+    strip_params(&mut cleaned, params);
+    if cleaned == url {
+        return None;
+    }
+    // Leave scheme-less links scheme-less when only their query changed
+    match cleaned.as_str().strip_prefix("https://") {
+        Some(rest) if !raw.contains("://") && cleaned.host_str() == url.host_str() => Some(rest.to_string()),
+        _ => Some(cleaned.to_string()),
+    }
+}
+
+/// Rewrite every URL in a text with [`clean_url`]
+pub fn clean_text(text: &str, params: &[impl AsRef<str>]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for span in spans(text) {
+        if let Some(cleaned) = clean_url(&text[span.clone()], params) {
+            out.push_str(&text[last..span.start]);
+            out.push_str(&cleaned);
+            last = span.end;
+        }
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Tracking parameter list from a user file: one name per line, added to the built-in
+/// [`TRACKING_PARAMS`], or removed from them when prefixed with `-`
+///
+/// Blank lines and `#` comments are ignored.
+pub fn tracking_params_from(text: &str) -> Vec<String> {
+    let mut params: Vec<String> = TRACKING_PARAMS.iter().map(|p| p.to_string()).collect();
+    for line in text.lines().map(|l| l.split('#').next().unwrap_or("").trim()) {
+        match line.strip_prefix('-') {
+            _ if line.is_empty() => {}
+            Some(removed) => params.retain(|p| !p.eq_ignore_ascii_case(removed.trim())),
+            None => {
+                let added = line.to_lowercase();
+                if !params.contains(&added) {
+                    params.push(added);
+                }
+            }
+        }
+    }
+    params
+}
+
+/// Whether a query parameter name is in `params`, where `prefix*` entries match any suffix
+pub fn is_listed_param(name: &str, params: &[impl AsRef<str>]) -> bool {
+    let name = name.to_lowercase();
+    params.iter().any(|param| match param.as_ref().strip_suffix('*') {
+        Some(prefix) => name.starts_with(&prefix.to_lowercase()),
+        None => name.eq_ignore_ascii_case(param.as_ref()),
+    })
+}
+
+/// Remove the query parameters listed in `params`, dropping the query if nothing is left
+///
+/// The kept parameters stay exactly as written, so signatures and valueless flags survive.
+pub fn strip_params(url: &mut Url, params: &[impl AsRef<str>]) {
+    let Some(query) = url.query() else {
+        return;
+    };
+    let listed = |segment: &str| {
+        form_urlencoded::parse(segment.as_bytes()).next().is_some_and(|(name, _)| is_listed_param(&name, params))
+    };
+    if !query.split('&').any(listed) {
+        return;
+    }
+    let kept: Vec<&str> = query.split('&').filter(|segment| !listed(segment)).collect();
+    let kept = kept.join("&");
+    url.set_query((!kept.is_empty()).then_some(kept.as_str()));
+}
+
+/// Rewrite shortened or mobile URLs whose target is implied by the URL itself
+///
+/// Nothing is fetched: shorteners that hide their target, such as `bit.ly`, stay as they are.
+fn expand(url: &Url) -> Option<Url> {
+    let host = url.host_str()?;
+    let first_segment = || url.path_segments()?.find(|s| !s.is_empty());
+
+    let expanded = match host {
+        "youtu.be" => format!("https://www.youtube.com/watch?v={}", first_segment()?),
+        "redd.it" => format!("https://www.reddit.com/comments/{}", first_segment()?),
+        "www.youtube.com" | "youtube.com" if url.path().starts_with("/shorts/") => {
+            format!("https://www.youtube.com/watch?v={}", url.path_segments()?.nth(1)?)
+        }
+        _ => {
+            let (_, canonical) = MOBILE_HOSTS.iter().find(|(mobile, _)| *mobile == host)?;
+            let mut url = url.clone();
+            url.set_host(Some(canonical)).ok()?;
+            return Some(url);
+        }
+    };
+
+    let mut expanded = Url::parse(&expanded).ok()?;
+    // Keep parameters such as a start time, after the ones the expansion added
+    let extra: Vec<(String, String)> = url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    if !extra.is_empty() {
+        expanded.query_pairs_mut().extend_pairs(extra);
+    }
+    Some(expanded)
+}
+
+/// Canonical form of a raw URL: redirect wrappers removed, lowercase host, no default port,
+/// fragment or tracking parameters, and known shortener and mobile hosts rewritten
+pub fn canonicalize(raw: &str) -> Option<Url> {
+    let mut url = unwrap_redirects(parse(raw)?);
+    if let Some(expanded) = expand(&url) {
+        url = expanded;
+    }
+    url.set_fragment(None);
+    strip_params(&mut url, TRACKING_PARAMS);
+    if let Some(host) = url.host_str().map(|h| h.trim_end_matches('.').to_string()) {
+        url.set_host(Some(&host)).ok()?;
+    }
+    Some(url)
+}
+
+/// Canonical URLs in a text, without duplicates, in order of appearance
+pub fn canonical_urls(text: &str) -> Vec<Url> {
+    let mut urls: Vec<Url> = Vec::new();
+    for url in extract(text).into_iter().filter_map(canonicalize) {
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// Set the `urls` and `domains` metadata keys from the links in the text and metadata
+pub fn annotate(content: &mut Content) {
+    // Metadata in key order so the result doesn't depend on hash order
+    let mut metadata: Vec<(&String, &String)> = content
+        .metadata
+        .iter()
+        .filter(|(key, _)| !matches!(key.as_str(), URLS_KEY | DOMAINS_KEY))
+        .collect();
+    metadata.sort();
+    let mut sources: Vec<&str> = vec![&content.text];
+    sources.extend(metadata.into_iter().map(|(_, value)| value.as_str()));
+    let mut urls: Vec<Url> = Vec::new();
+    for url in sources.into_iter().flat_map(canonical_urls) {
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    if urls.is_empty() {
+        return;
+    }
+
+    let mut domains: Vec<&str> = Vec::new();
+    for host in urls.iter().filter_map(Url::host_str) {
+        if !domains.contains(&host) {
+            domains.push(host);
+        }
+    }
+    let domains = domains.join(" ");
+    let urls = urls.iter().map(Url::as_str).collect::<Vec<_>>().join(" ");
+    content.metadata.insert(URLS_KEY.to_string(), urls);
+    content.metadata.insert(DOMAINS_KEY.to_string(), domains);
+}
+
+/// Hosts linked from content: from its `domains` metadata, otherwise extracted from its text
+pub fn domains_of(content: &Content) -> Vec<String> {
+    match content.metadata.get(DOMAINS_KEY) {
+        Some(domains) => domains.split_whitespace().map(str::to_string).collect(),
+        None => canonical_urls(&content.text)
+            .iter()
+            .filter_map(|url| url.host_str().map(str::to_string))
+            .collect(),
+    }
+}
+
+/// Whether a domain pattern is well formed: a host, optionally behind a leading `*.`, or `*`
+pub fn is_valid_pattern(pattern: &str) -> bool {
+    let domain = match pattern.strip_prefix("*") {
+        Some("") => return true,
+        Some(rest) => match rest.strip_prefix('.') {
+            Some(domain) => domain,
+            None => return false,
+        },
+        None => pattern,
+    };
+    !domain.is_empty()
+        && domain
+            .split('.')
+            .all(|label| !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_canonicalize() {
+        let canonical = |raw: &str| canonicalize(raw).map(|url| url.to_string());
+
+        assert_eq!(
+            canonical("HTTPS://News.Example.COM:443/story?id=7&utm_source=feed&UTM_Medium=x&fbclid=abc#comments").as_deref(),
+            Some("https://news.example.com/story?id=7")
+        );
+        assert_eq!(canonical("https://youtu.be/dQw4w9WgXcQ?t=42&si=xyz").as_deref(), Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42"));
+        assert_eq!(canonical("https://m.youtube.com/watch?v=abc").as_deref(), Some("https://www.youtube.com/watch?v=abc"));
+        assert_eq!(canonical("www.example.com/a").as_deref(), Some("https://www.example.com/a"));
+        assert_eq!(canonical("https://bit.ly/3xyz").as_deref(), Some("https://bit.ly/3xyz"));
+        assert_eq!(canonical("ftp://example.com/file"), None);
+    }
+
+    #[test]
+    fn test_extract_and_annotate() {
+        assert_eq!(
+            extract("See https://en.wikipedia.org/wiki/Rust_(programming_language), and (www.example.com/x)."),
+            vec!["https://en.wikipedia.org/wiki/Rust_(programming_language)", "www.example.com/x"]
+        );
+
+        let mut content = Content {
+            id: "test".to_string(),
+            text: "Read https://example.com/a?utm_campaign=x then https://EXAMPLE.com/a".to_string(),
+            view_duration: 0,
+            metadata: HashMap::from([("link".to_string(), "https://blog.example.org/post".to_string())]),
+            flags: vec![],
+            score: 0.0,
+        };
+        annotate(&mut content);
+        assert_eq!(content.metadata[URLS_KEY], "https://example.com/a https://blog.example.org/post");
+        assert_eq!(domains_of(&content), vec!["example.com", "blog.example.org"]);
+    }
+
+    #[test]
+    fn test_clean_text() {
+        let text = "Via https://www.google.com/url?q=https%3A%2F%2Fexample.com%2Fa%3Fid%3D1%26utm_source%3Dg&sa=D \
+                    and www.shop.example/item?ref=feed&color=red#top, untouched https://example.com/b?id=2.";
+        let params = tracking_params_from("# ours\nref\n-si\n");
+        assert!(params.contains(&"ref".to_string()) && !params.contains(&"si".to_string()));
+
+        assert_eq!(
+            clean_text(text, &params),
+            "Via https://example.com/a?id=1 and www.shop.example/item?color=red#top, untouched https://example.com/b?id=2."
+        );
+        assert_eq!(clean_url("https://example.com/?UTM_Source=x", TRACKING_PARAMS).as_deref(), Some("https://example.com/"));
+        // Kept parameters aren't re-encoded
+        assert_eq!(
+            clean_url("https://example.com/search?q=a/b+c%20d&flag&utm_source=x&sig=AB%2Bc", TRACKING_PARAMS).as_deref(),
+            Some("https://example.com/search?q=a/b+c%20d&flag&sig=AB%2Bc")
+        );
+    }
+
+    #[test]
+    fn test_domain_patterns() {
+        assert!(is_valid_pattern("example.com"));
+        assert!(is_valid_pattern("*.example.co.uk"));
+        assert!(!is_valid_pattern("news.*.com"));
+        assert!(!is_valid_pattern("https://example.com"));
+    }
+}
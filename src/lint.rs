@@ -0,0 +1,531 @@
+use crate::content::{ActionType, ConditionType, PluginRegistry, Rule};
+use chrono::Utc;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Compiled size above which a regex is reported as too large
+const REGEX_SIZE_WARNING: usize = 1 << 20;
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The rule works but is probably not doing what was intended
+    Warning,
+    /// The rule cannot work as written
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Kinds of problems the linter detects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintKind {
+    /// Same condition as an earlier rule
+    Duplicate,
+    /// An earlier rule always matches first
+    Unreachable,
+    /// Regex fails to compile
+    InvalidRegex,
+    /// Regex with nested quantifiers or a very large compiled size
+    CatastrophicRegex,
+    /// Condition matches every item
+    MatchesEverything,
+    /// Modify template discards the original text
+    MissingContentPlaceholder,
+    /// ML rule references a model that isn't installed
+    MissingModel,
+    /// ML or similarity threshold outside 0.0..=1.0
+    InvalidThreshold,
+    /// Rate limit that can never let an item through
+    InvalidRateLimit,
+    /// Quarantine delay that is not positive
+    InvalidDelay,
+    /// Link domain pattern that can never match a host
+    InvalidDomainPattern,
+    /// Reputation condition on a metadata key that has no reputation
+    UntrackedReputation,
+    /// Summary that keeps no sentences
+    EmptySummary,
+    /// Plugin condition or action that isn't registered
+    UnknownPlugin,
+    /// Schedule cannot be evaluated
+    InvalidSchedule,
+    /// Schedule validity period has ended
+    ExpiredSchedule,
+}
+
+/// A single lint finding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintIssue {
+    /// Rule the finding is about
+    pub rule_id: String,
+    /// How serious the finding is
+    pub severity: Severity,
+    /// Kind of problem
+    pub kind: LintKind,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.rule_id, self.message)
+    }
+}
+
+/// Analyse a rule set for duplicates, shadowed rules and broken conditions or actions
+///
+/// Rules are considered in evaluation order (priority descending, then ID). `models` holds
+/// the IDs of installed ML models; plugin names are checked against `plugins`.
+pub fn lint_rules(rules: &[Rule], models: &HashSet<String>, plugins: &PluginRegistry) -> Vec<LintIssue> {
+    let mut ordered: Vec<&Rule> = rules.iter().collect();
+    ordered.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+
+    // Without any registered plugins, plugin rules may just be meant for another host
+    let plugin_severity = if plugins.is_empty() { Severity::Warning } else { Severity::Error };
+
+    let mut issues = Vec::new();
+    let mut seen_conditions: HashMap<String, &str> = HashMap::new();
+    // Earlier unscheduled rules, which always get the first chance to match
+    let mut always_keywords: Vec<(&str, String)> = Vec::new();
+    let mut always_domains: HashMap<String, &str> = HashMap::new();
+
+    for rule in ordered {
+        let mut issue = |severity, kind, message: String| {
+            issues.push(LintIssue {
+                rule_id: rule.id.clone(),
+                severity,
+                kind,
+                message,
+            })
+        };
+
+        let key = rule.condition.key();
+        if let Some(first) = seen_conditions.get(&key) {
+            issue(
+                Severity::Warning,
+                LintKind::Duplicate,
+                format!("same condition as rule '{}', which is evaluated first", first),
+            );
+        } else if let Some(shadow) = shadowing_rule(&rule.condition, &always_keywords, &always_domains) {
+            issue(
+                Severity::Warning,
+                LintKind::Unreachable,
+                format!("never reached: rule '{}' matches everything this rule matches and runs first", shadow),
+            );
+        }
+        // A scheduled rule leaves later ones to match while it's inactive
+        if rule.schedule.is_none() && rule.action.stops_evaluation() {
+            seen_conditions.entry(key).or_insert(&rule.id);
+        }
+
+        for condition in rule.condition.walk() {
+            match condition {
+                ConditionType::Keyword(keyword) if keyword.is_empty() => {
+                    issue(Severity::Warning, LintKind::MatchesEverything, "empty keyword matches every item".to_string());
+                }
+                ConditionType::Regex(pattern) => lint_regex(pattern, &mut issue),
+                ConditionType::MachineLearning { model_id, threshold } => {
+                    if !models.contains(model_id) {
+                        issue(Severity::Error, LintKind::MissingModel, format!("model '{}' is not installed", model_id));
+                    }
+                    if !(0.0..=1.0).contains(threshold) {
+                        issue(
+                            Severity::Error,
+                            LintKind::InvalidThreshold,
+                            format!("threshold {} is outside 0.0..=1.0", threshold),
+                        );
+                    }
+                }
+                ConditionType::Feature { feature, value, .. } if feature.is_ratio() && !(0.0..=1.0).contains(value) => {
+                    issue(
+                        Severity::Warning,
+                        LintKind::InvalidThreshold,
+                        format!("{} is a ratio between 0.0 and 1.0, compared against {}", feature, value),
+                    );
+                }
+                ConditionType::Clickbait { threshold } if !(0.0..=1.0).contains(threshold) => {
+                    issue(
+                        Severity::Error,
+                        LintKind::InvalidThreshold,
+                        format!("clickbait threshold {} is outside 0.0..=1.0", threshold),
+                    );
+                }
+                ConditionType::LinksTo { domains } | ConditionType::LinksOutside { domains } => {
+                    for pattern in domains.iter().filter(|p| !crate::links::is_valid_pattern(p)) {
+                        issue(
+                            Severity::Error,
+                            LintKind::InvalidDomainPattern,
+                            format!("'{}' is not a host, '*.host' or '*'", pattern),
+                        );
+                    }
+                }
+                ConditionType::Reputation { key, value, .. } => {
+                    if !crate::reputation::TRACKED_KEYS.contains(&key.as_str()) {
+                        issue(
+                            Severity::Warning,
+                            LintKind::UntrackedReputation,
+                            format!(
+                                "'{}' has no reputation; only {} are tracked",
+                                key,
+                                crate::reputation::TRACKED_KEYS.join(" and ")
+                            ),
+                        );
+                    }
+                    if !(0.0..=1.0).contains(value) {
+                        issue(
+                            Severity::Warning,
+                            LintKind::InvalidThreshold,
+                            format!("reputation is between 0.0 and 1.0, compared against {}", value),
+                        );
+                    }
+                }
+                ConditionType::Sentiment { value, .. } if !(-1.0..=1.0).contains(value) => {
+                    issue(
+                        Severity::Warning,
+                        LintKind::InvalidThreshold,
+                        format!("sentiment is between -1.0 and 1.0, compared against {}", value),
+                    );
+                }
+                ConditionType::Toxicity { threshold } if !(0.0..=1.0).contains(threshold) => {
+                    issue(
+                        Severity::Error,
+                        LintKind::InvalidThreshold,
+                        format!("toxicity threshold {} is outside 0.0..=1.0", threshold),
+                    );
+                }
+                ConditionType::Language { min_confidence, .. } if !(0.0..=1.0).contains(min_confidence) => {
+                    issue(
+                        Severity::Error,
+                        LintKind::InvalidThreshold,
+                        format!("language confidence {} is outside 0.0..=1.0", min_confidence),
+                    );
+                }
+                ConditionType::NearDuplicate { threshold, .. } if !(0.0..=1.0).contains(threshold) => {
+                    issue(
+                        Severity::Error,
+                        LintKind::InvalidThreshold,
+                        format!("similarity threshold {} is outside 0.0..=1.0", threshold),
+                    );
+                }
+                ConditionType::Plugin { name, .. } if plugins.condition(name).is_none() => {
+                    issue(plugin_severity, LintKind::UnknownPlugin, format!("condition plugin '{}' is not registered", name));
+                }
+                _ => {}
+            }
+        }
+
+        match &rule.action {
+            ActionType::Modify { transform } if !transform.contains("{content}") => {
+                issue(
+                    Severity::Warning,
+                    LintKind::MissingContentPlaceholder,
+                    "modify template has no {content} placeholder, so the original text is discarded".to_string(),
+                );
+            }
+            ActionType::RateLimit { max, window_hours } if *max == 0 || *window_hours <= 0 => {
+                issue(
+                    Severity::Error,
+                    LintKind::InvalidRateLimit,
+                    format!("rate limit of {} per {}h can never let an item through", max, window_hours),
+                );
+            }
+            ActionType::Quarantine { delay_hours, .. } if *delay_hours <= 0 => {
+                issue(
+                    Severity::Warning,
+                    LintKind::InvalidDelay,
+                    format!("delay of {}h releases items immediately", delay_hours),
+                );
+            }
+            ActionType::Summarize { sentences: 0 } => {
+                issue(
+                    Severity::Error,
+                    LintKind::EmptySummary,
+                    "summary of 0 sentences would discard the text".to_string(),
+                );
+            }
+            ActionType::Plugin { name, .. } if plugins.action(name).is_none() => {
+                issue(plugin_severity, LintKind::UnknownPlugin, format!("action plugin '{}' is not registered", name));
+            }
+            _ => {}
+        }
+
+        if let Some(schedule) = &rule.schedule {
+            if let Err(e) = schedule.validate() {
+                issue(Severity::Error, LintKind::InvalidSchedule, e.to_string());
+            } else if schedule.valid_until.is_some_and(|until| until < Utc::now().date_naive()) {
+                issue(
+                    Severity::Warning,
+                    LintKind::ExpiredSchedule,
+                    format!("schedule expired on {}", schedule.valid_until.unwrap_or_default()),
+                );
+            }
+        }
+
+        // Scoring rules let later rules run, so they shadow nothing
+        if rule.schedule.is_none() && rule.action.stops_evaluation() {
+            match &rule.condition {
+                ConditionType::Keyword(keyword) => always_keywords.push((&rule.id, keyword.to_lowercase())),
+                ConditionType::Domain(domain) => {
+                    always_domains.entry(normalize_domain(domain)).or_insert(&rule.id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    issues
+}
+
+/// Find an earlier always-active rule whose condition is implied by `condition`
+fn shadowing_rule<'a>(
+    condition: &ConditionType,
+    keywords: &[(&'a str, String)],
+    domains: &HashMap<String, &'a str>,
+) -> Option<&'a str> {
+    match condition {
+        // Any text containing the longer keyword also contains the shorter one
+        ConditionType::Keyword(keyword) => {
+            let keyword = keyword.to_lowercase();
+            keywords
+                .iter()
+                .find(|(_, earlier)| keyword.contains(earlier.as_str()))
+                .map(|(id, _)| *id)
+        }
+        // A parent domain matches all of its subdomains
+        ConditionType::Domain(domain) => {
+            let domain = normalize_domain(domain);
+            let mut suffix = domain.as_str();
+            loop {
+                if let Some(id) = domains.get(suffix) {
+                    return Some(*id);
+                }
+                suffix = suffix.split_once('.')?.1;
+            }
+        }
+        _ => None,
+    }
+}
+
+fn normalize_domain(domain: &str) -> String {
+    domain.strip_prefix("*.").unwrap_or(domain).trim_end_matches('.').to_lowercase()
+}
+
+fn lint_regex(pattern: &str, issue: &mut impl FnMut(Severity, LintKind, String)) {
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(e) => {
+            issue(Severity::Error, LintKind::InvalidRegex, format!("invalid regex: {}", e));
+            return;
+        }
+    };
+
+    if regex.is_match("") {
+        issue(
+            Severity::Warning,
+            LintKind::MatchesEverything,
+            format!("regex /{}/ matches the empty string, so it matches every item", pattern),
+        );
+    }
+
+    if has_nested_quantifier(pattern) {
+        issue(
+            Severity::Warning,
+            LintKind::CatastrophicRegex,
+            format!("regex /{}/ nests quantifiers and would backtrack catastrophically outside this engine", pattern),
+        );
+    }
+
+    if RegexBuilder::new(pattern).size_limit(REGEX_SIZE_WARNING).build().is_err() {
+        issue(
+            Severity::Warning,
+            LintKind::CatastrophicRegex,
+            format!("regex /{}/ compiles to a very large program and will be slow", pattern),
+        );
+    }
+}
+
+/// Detect a quantified group that itself contains a quantifier, e.g. `(a+)+`
+fn has_nested_quantifier(pattern: &str) -> bool {
+    // One entry per open group: whether it contains a quantifier
+    let mut groups: Vec<bool> = Vec::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => {
+                // Skip character classes, which can't contain groups
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        ']' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '(' => groups.push(false),
+            ')' => {
+                let inner = groups.pop().unwrap_or(false);
+                if inner && matches!(chars.peek(), Some('*' | '+' | '{')) {
+                    return true;
+                }
+            }
+            '*' | '+' | '{' => {
+                if let Some(quantified) = groups.last_mut() {
+                    *quantified = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::{parse_days, Schedule};
+
+    fn rule(id: &str, condition: ConditionType, action: ActionType) -> Rule {
+        Rule {
+            id: id.to_string(),
+            condition,
+            action,
+            priority: 0,
+            schedule: None,
+        }
+    }
+
+    fn kinds(issues: &[LintIssue], rule_id: &str) -> Vec<LintKind> {
+        issues.iter().filter(|i| i.rule_id == rule_id).map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn test_duplicates_and_shadowing() {
+        let rules = vec![
+            rule("a-crypto", ConditionType::Keyword("crypto".to_string()), ActionType::Filter),
+            rule("b-crypto", ConditionType::Keyword("Crypto".to_string()), ActionType::Filter),
+            rule("c-cryptocurrency", ConditionType::Keyword("cryptocurrency".to_string()), ActionType::Filter),
+            rule("d-domain", ConditionType::Domain("example.com".to_string()), ActionType::Filter),
+            rule("e-subdomain", ConditionType::Domain("news.example.com".to_string()), ActionType::Filter),
+        ];
+
+        let issues = lint_rules(&rules, &HashSet::new(), &PluginRegistry::new());
+        assert_eq!(kinds(&issues, "a-crypto"), vec![]);
+        assert_eq!(kinds(&issues, "b-crypto"), vec![LintKind::Duplicate]);
+        assert_eq!(kinds(&issues, "c-cryptocurrency"), vec![LintKind::Unreachable]);
+        assert_eq!(kinds(&issues, "e-subdomain"), vec![LintKind::Unreachable]);
+    }
+
+    #[test]
+    fn test_priority_changes_reachability() {
+        let mut specific = rule("specific", ConditionType::Keyword("cryptocurrency".to_string()), ActionType::Filter);
+        specific.priority = 10;
+        let rules = vec![
+            rule("general", ConditionType::Keyword("crypto".to_string()), ActionType::Filter),
+            specific,
+        ];
+
+        assert!(lint_rules(&rules, &HashSet::new(), &PluginRegistry::new()).is_empty());
+    }
+
+    #[test]
+    fn test_scheduled_rule_is_no_duplicate() {
+        let mut work_hours = rule("a-work", ConditionType::Keyword("sports".to_string()), ActionType::Filter);
+        work_hours.schedule = Some(Schedule {
+            days: parse_days("weekdays").unwrap(),
+            time_ranges: vec!["09:00-17:00".parse().unwrap()],
+            ..Schedule::default()
+        });
+        let rules = vec![
+            work_hours,
+            rule("b-always", ConditionType::Keyword("sports".to_string()), ActionType::Flag { flags: vec!["sports".to_string()] }),
+            rule("c-again", ConditionType::Keyword("sports".to_string()), ActionType::Filter),
+        ];
+
+        let issues = lint_rules(&rules, &HashSet::new(), &PluginRegistry::new());
+        assert_eq!(kinds(&issues, "b-always"), vec![]);
+        assert_eq!(kinds(&issues, "c-again"), vec![LintKind::Duplicate]);
+    }
+
+    #[test]
+    fn test_broken_conditions_and_actions() {
+        let rules = vec![
+            rule("bad-regex", ConditionType::Regex("(unclosed".to_string()), ActionType::Filter),
+            rule("nested", ConditionType::Regex("(a+)+b".to_string()), ActionType::Filter),
+            rule("everything", ConditionType::Regex("x*".to_string()), ActionType::Filter),
+            rule(
+                "template",
+                ConditionType::Keyword("spoiler".to_string()),
+                ActionType::Modify { transform: "[hidden]".to_string() },
+            ),
+            rule(
+                "model",
+                ConditionType::MachineLearning { model_id: "clickbait".to_string(), threshold: 1.5 },
+                ActionType::Filter,
+            ),
+        ];
+
+        let issues = lint_rules(&rules, &HashSet::new(), &PluginRegistry::new());
+        assert_eq!(kinds(&issues, "bad-regex"), vec![LintKind::InvalidRegex]);
+        assert_eq!(kinds(&issues, "nested"), vec![LintKind::CatastrophicRegex]);
+        assert_eq!(kinds(&issues, "everything"), vec![LintKind::MatchesEverything]);
+        assert_eq!(kinds(&issues, "template"), vec![LintKind::MissingContentPlaceholder]);
+        assert_eq!(kinds(&issues, "model"), vec![LintKind::MissingModel, LintKind::InvalidThreshold]);
+    }
+
+    #[test]
+    fn test_nested_quantifier_detection() {
+        assert!(has_nested_quantifier("(a+)+"));
+        assert!(has_nested_quantifier(r"(\w*\s?)*$"));
+        assert!(has_nested_quantifier("((ab)+c)*"));
+        assert!(!has_nested_quantifier("(ab)+c*"));
+        assert!(!has_nested_quantifier(r"\(a+\)+"));
+        assert!(!has_nested_quantifier("([)+]x)+"));
+    }
+
+    struct Always;
+
+    #[async_trait::async_trait]
+    impl crate::content::ConditionPlugin for Always {
+        async fn matches(&self, _: &crate::content::Content, _: &serde_json::Value) -> anyhow::Result<bool> {
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn test_unknown_plugin_severity() {
+        let rules = vec![rule(
+            "plugin",
+            ConditionType::Plugin { name: "missing".to_string(), config: serde_json::Value::Null },
+            ActionType::Filter,
+        )];
+
+        // No plugins registered at all: the rule may be meant for a host that has them
+        let issues = lint_rules(&rules, &HashSet::new(), &PluginRegistry::new());
+        assert_eq!(kinds(&issues, "plugin"), vec![LintKind::UnknownPlugin]);
+        assert_eq!(issues[0].severity, Severity::Warning);
+
+        let plugins = PluginRegistry::new();
+        plugins.register_condition("always", Always);
+        let issues = lint_rules(&rules, &HashSet::new(), &plugins);
+        assert_eq!(issues[0].severity, Severity::Error);
+    }
+}
@@ -1,11 +1,24 @@
 use anyhow::Result;
+use chrono::NaiveDate;
 use clap::{Parser, Subcommand};
 use sap::{
-    content::{ActionType, ConditionType, Content, Rule},
+    allowlist::{AllowEntry, AllowKind},
+    attention,
+    blocklist::ListFormat,
+    content::{group_by_flag, ActionType, ConditionType, Content, Rule, RuleStats},
+    dsl,
+    fixture::TestSuite,
+    lexicon::Lexicon,
+    links,
+    lint::Severity,
+    rulefile::RuleFile,
+    schedule::{parse_days, parse_timezone, Schedule},
     LocalProcessor,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tracing::{error, info};
 
 #[derive(Parser)]
@@ -24,7 +37,7 @@ enum Commands {
         #[arg(short, long)]
         id: String,
         
-        /// Condition type (keyword, regex, ml)
+        /// Condition type (keyword, regex, domain, ml, duplicate, feature, clickbait, sentiment, toxicity, lang, links_to, links_outside, reputation)
         #[arg(short, long)]
         condition_type: String,
         
@@ -32,17 +45,95 @@ enum Commands {
         #[arg(short, long)]
         value: String,
         
-        /// Action type (filter, modify, flag)
+        /// Action type (filter, modify, flag, clean-links, summarize, boost, penalize, delay, mark-duplicate, collapse, limit)
         #[arg(short, long)]
         action: String,
         
         /// Action parameters as JSON string
         #[arg(short, long)]
         params: Option<String>,
+
+        /// Evaluation priority (higher runs first)
+        #[arg(long, default_value = "0")]
+        priority: i32,
+
+        /// Days the rule is active on (e.g. mon,tue or weekdays)
+        #[arg(long)]
+        days: Option<String>,
+
+        /// Time windows the rule is active in (e.g. 09:00-17:00,22:00-06:00)
+        #[arg(long)]
+        hours: Option<String>,
+
+        /// Timezone for the schedule (local, UTC or +HH:MM); fixed offsets ignore daylight
+        /// saving time, so use local to follow the system clock's changes
+        #[arg(long)]
+        timezone: Option<String>,
+
+        /// First date the rule is valid on (YYYY-MM-DD)
+        #[arg(long)]
+        valid_from: Option<NaiveDate>,
+
+        /// Last date the rule is valid on (YYYY-MM-DD)
+        #[arg(long)]
+        valid_until: Option<NaiveDate>,
+    },
+
+    /// Score a feed and print the kept items ordered by score, highest first
+    Rank {
+        /// Feed of content items, one JSON object per line
+        #[arg(short, long)]
+        input: PathBuf,
+    },
+
+    /// Show queued content that is due, grouped by flag
+    Digest {
+        /// Remove the shown items from the queue
+        #[arg(long)]
+        release: bool,
+
+        /// Include items that are not due yet
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Explain the clickbait score of a text
+    Clickbait {
+        /// Text to score, e.g. a headline
+        text: String,
+    },
+
+    /// Add a rule written in the rule DSL
+    ///
+    /// Example: when text ~ /spoiler/i and meta.source = "reddit" then flag spoiler
+    Add {
+        /// Rule source, e.g. `rule spoilers priority 5 when text contains "ending" then filter`
+        rule: String,
+
+        /// Rule identifier, if the rule has no `rule <id>` header
+        #[arg(short, long)]
+        id: Option<String>,
     },
 
     /// List all content filtering rules
-    ListRules,
+    ListRules {
+        /// Show per-rule hit statistics
+        #[arg(long)]
+        stats: bool,
+    },
+
+    /// Check rules for duplicates, shadowed rules and broken conditions
+    LintRules {
+        /// Directory of installed ML models (defaults to the models directory next to the database)
+        #[arg(long)]
+        models_dir: Option<PathBuf>,
+    },
+
+    /// Run rule test fixtures and report differences
+    TestRules {
+        /// Fixture file (JSON)
+        file: PathBuf,
+    },
 
     /// View attention metrics
     Metrics {
@@ -90,6 +181,227 @@ enum Commands {
         #[arg(short, long)]
         input: PathBuf,
     },
+
+    /// Import a keyword or domain blocklist as rules
+    ImportList {
+        /// List file path
+        file: PathBuf,
+
+        /// List format (plain, adblock, hosts)
+        #[arg(short, long, default_value = "plain")]
+        format: String,
+
+        /// List identifier (defaults to the file name); re-importing replaces the list's rules
+        #[arg(short, long)]
+        id: Option<String>,
+
+        /// Flag matching content with this flag instead of filtering it
+        #[arg(long)]
+        flag: Option<String>,
+    },
+
+    /// List imported blocklists
+    Lists,
+
+    /// Sync rules from a TOML, YAML or JSON rules file into the rule store
+    SyncRules {
+        /// Rules file path
+        file: PathBuf,
+    },
+
+    /// Process newline-delimited JSON content from stdin, printing kept items to stdout
+    Serve {
+        /// Rules file to load and watch for changes
+        #[arg(long)]
+        rules_file: Option<PathBuf>,
+
+        /// Seconds between checks of the rules file
+        #[arg(long, default_value = "2")]
+        poll_interval: u64,
+    },
+
+    /// Manage rule profiles
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+
+    /// Show or adjust author and source reputations
+    Reputation {
+        #[command(subcommand)]
+        command: ReputationCommands,
+    },
+
+    /// Report on how attention is spent over time
+    Report {
+        #[command(subcommand)]
+        command: ReportCommands,
+    },
+
+    /// Manage the allowlist of content that passes whatever rules match it
+    Allow {
+        #[command(subcommand)]
+        command: AllowCommands,
+    },
+
+    /// Explain how the rules treat a piece of content, without recording anything
+    Explain {
+        /// Content identifier
+        #[arg(short, long, default_value = "explain")]
+        id: String,
+
+        /// Content text
+        #[arg(short, long)]
+        text: String,
+
+        /// Metadata as key=value, e.g. author=alice (repeatable)
+        #[arg(short, long = "meta")]
+        meta: Vec<String>,
+
+        /// Flags already on the content (repeatable)
+        #[arg(short, long = "flag")]
+        flag: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReportCommands {
+    /// Group attention into reading sessions and show how focused they were
+    Sessions {
+        /// Minutes without attention that end a session
+        #[arg(short, long, default_value_t = attention::DEFAULT_IDLE_GAP_SECS / 60)]
+        idle_gap: i64,
+
+        /// Only include attention from the last N days
+        #[arg(short, long, default_value = "7")]
+        days: i64,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show attention by day of the week and hour of the day
+    Heatmap {
+        /// Only include attention from the last N days
+        #[arg(short, long, default_value = "28")]
+        days: i64,
+
+        /// Timezone to bucket hours in (local, UTC or +HH:MM)
+        #[arg(short, long, default_value = "local")]
+        timezone: String,
+
+        /// Output format (ascii, json, csv)
+        #[arg(short, long, default_value = "ascii")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AllowCommands {
+    /// Add an allowlist entry
+    Add {
+        /// What to match (id, author, domain or flag)
+        kind: AllowKind,
+
+        /// Content ID pattern (`*` matches anything), author, domain or flag
+        pattern: String,
+    },
+
+    /// Remove an allowlist entry
+    Remove {
+        /// What the entry matches (id, author, domain or flag)
+        kind: AllowKind,
+
+        /// Pattern of the entry
+        pattern: String,
+    },
+
+    /// List allowlist entries
+    List,
+}
+
+#[derive(Subcommand)]
+enum ReputationCommands {
+    /// List reputations, highest score first
+    List {
+        /// Only show this metadata key (author or source)
+        #[arg(short, long)]
+        key: Option<String>,
+    },
+
+    /// Set a reputation score by hand, overriding the one computed from attention
+    Set {
+        /// Metadata key (author or source)
+        key: String,
+
+        /// Metadata value, e.g. the author's name
+        value: String,
+
+        /// Score between 0.0 and 1.0
+        score: f64,
+    },
+
+    /// Forget reputations, or only remove a manual score with --manual
+    Reset {
+        /// Metadata key (author or source)
+        key: String,
+
+        /// Metadata value; resets every reputation under the key if omitted
+        value: Option<String>,
+
+        /// Keep the collected evidence and only remove the manual score
+        #[arg(long, requires = "value")]
+        manual: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// Create a new profile
+    Create {
+        /// Profile name
+        name: String,
+    },
+
+    /// Delete a profile
+    Delete {
+        /// Profile name
+        name: String,
+    },
+
+    /// Add a rule to a profile
+    AddRule {
+        /// Profile name
+        profile: String,
+
+        /// Rule identifier
+        rule_id: String,
+    },
+
+    /// Remove a rule from a profile
+    RemoveRule {
+        /// Profile name
+        profile: String,
+
+        /// Rule identifier
+        rule_id: String,
+    },
+
+    /// Switch to a profile's rule set
+    Use {
+        /// Profile name
+        name: String,
+    },
+
+    /// Deactivate the current profile and apply all rules
+    Clear,
+
+    /// List all profiles
+    List,
+
+    /// Compare attention metrics across profiles
+    Metrics,
 }
 
 #[tokio::main]
@@ -110,6 +422,29 @@ async fn main() -> Result<()> {
     
     let processor = LocalProcessor::new(&database_url).await?;
 
+    // A lexicon file next to the database replaces the built-in clickbait lexicon
+    let lexicon_path = db_path.with_file_name("lexicon.toml");
+    if lexicon_path.exists() {
+        processor.set_lexicon(Lexicon::load(&lexicon_path)?).await;
+    }
+
+    // Tracking parameters to add to or remove from the built-in list, one per line
+    let params_path = db_path.with_file_name("tracking-params.txt");
+    if params_path.exists() {
+        let params = std::fs::read_to_string(&params_path)?;
+        processor.set_tracking_params(links::tracking_params_from(&params)).await;
+    }
+
+    // Register WASM condition plugins installed next to the database
+    #[cfg(feature = "wasm")]
+    {
+        let plugins_dir = db_path.with_file_name("plugins");
+        if plugins_dir.is_dir() {
+            let names = processor.load_wasm_plugins(&plugins_dir, sap::wasm::WasmLimits::default())?;
+            tracing::debug!("Loaded WASM plugins: {}", names.join(", "));
+        }
+    }
+
     match cli.command {
         Commands::AddRule {
             id,
@@ -117,14 +452,68 @@ async fn main() -> Result<()> {
             value,
             action,
             params,
+            priority,
+            days,
+            hours,
+            timezone,
+            valid_from,
+            valid_until,
         } => {
             let condition = match condition_type.as_str() {
                 "keyword" => ConditionType::Keyword(value),
                 "regex" => ConditionType::Regex(value),
+                "domain" => ConditionType::Domain(value),
                 "ml" => ConditionType::MachineLearning {
                     model_id: value,
                     threshold: 0.5,
                 },
+                "clickbait" => ConditionType::Clickbait { threshold: value.parse()? },
+                // e.g. "exclamations >= 3"
+                "feature" => match value.split_whitespace().collect::<Vec<_>>()[..] {
+                    [feature, op, number] => ConditionType::Feature {
+                        feature: feature.parse()?,
+                        op: op.parse()?,
+                        value: number.parse()?,
+                    },
+                    _ => anyhow::bail!("Feature conditions look like \"exclamations >= 3\""),
+                },
+                // e.g. "youtube.com,youtu.be"
+                "links_to" | "links_outside" => {
+                    let domains = value.split(',').map(|d| d.trim().to_string()).collect();
+                    if condition_type == "links_to" {
+                        ConditionType::LinksTo { domains }
+                    } else {
+                        ConditionType::LinksOutside { domains }
+                    }
+                }
+                // e.g. "author < 0.3"
+                "reputation" => match value.split_whitespace().collect::<Vec<_>>()[..] {
+                    [key, op, number] => ConditionType::Reputation {
+                        key: key.to_string(),
+                        op: op.parse()?,
+                        value: number.parse()?,
+                    },
+                    _ => anyhow::bail!("Reputation conditions look like \"author < 0.3\""),
+                },
+                // e.g. "< -0.5"
+                "sentiment" => match value.split_whitespace().collect::<Vec<_>>()[..] {
+                    [op, number] => ConditionType::Sentiment { op: op.parse()?, value: number.parse()? },
+                    _ => anyhow::bail!("Sentiment conditions look like \"< -0.5\""),
+                },
+                "toxicity" => ConditionType::Toxicity { threshold: value.parse()? },
+                // e.g. "de" or "de 0.5"
+                "lang" => match value.split_whitespace().collect::<Vec<_>>()[..] {
+                    [code] => ConditionType::Language { code: code.to_string(), min_confidence: 0.0 },
+                    [code, confidence] => ConditionType::Language {
+                        code: code.to_string(),
+                        min_confidence: confidence.parse()?,
+                    },
+                    _ => anyhow::bail!("Language conditions look like \"de\" or \"de 0.5\""),
+                },
+                "duplicate" => ConditionType::NearDuplicate {
+                    threshold: value.parse()?,
+                    window_hours: None,
+                },
                 _ => anyhow::bail!("Invalid condition type"),
             };
 
@@ -140,33 +529,162 @@ async fn main() -> Result<()> {
                         .unwrap_or_else(|| vec!["flagged".to_string()]);
                     ActionType::Flag { flags }
                 }
+                "boost" | "penalize" => {
+                    let weight: f64 = params.map(|p| p.parse()).transpose()?.unwrap_or(1.0);
+                    ActionType::Score {
+                        weight: if action == "penalize" { -weight } else { weight },
+                    }
+                }
+                "clean-links" => ActionType::CleanLinks {
+                    params: params.map(|p| serde_json::from_str(&p)).transpose()?.unwrap_or_default(),
+                },
+                "summarize" => ActionType::Summarize {
+                    sentences: params.map(|p| p.parse()).transpose()?.unwrap_or(3),
+                },
+                "mark-duplicate" => ActionType::MarkDuplicate { collapse: false },
+                "collapse" => ActionType::MarkDuplicate { collapse: true },
+                "delay" => {
+                    let params = params.ok_or_else(|| anyhow::anyhow!(r#"delay needs --params '{{"delay_hours": 24, "flags": ["news"]}}'"#))?;
+                    serde_json::from_value(serde_json::json!({ "Quarantine": serde_json::from_str::<serde_json::Value>(&params)? }))?
+                }
+                "limit" => {
+                    let params = params.ok_or_else(|| anyhow::anyhow!(r#"limit needs --params '{{"max": 5, "window_hours": 24}}'"#))?;
+                    serde_json::from_value(serde_json::json!({ "RateLimit": serde_json::from_str::<serde_json::Value>(&params)? }))?
+                }
                 _ => anyhow::bail!("Invalid action type"),
             };
 
+            let schedule = if days.is_some()
+                || hours.is_some()
+                || timezone.is_some()
+                || valid_from.is_some()
+                || valid_until.is_some()
+            {
+                Some(Schedule {
+                    days: days.map(|d| parse_days(&d)).transpose()?.unwrap_or_default(),
+                    time_ranges: hours
+                        .map(|h| h.split(',').map(str::parse).collect::<Result<Vec<_>>>())
+                        .transpose()?
+                        .unwrap_or_default(),
+                    timezone,
+                    valid_from,
+                    valid_until,
+                })
+            } else {
+                None
+            };
+
             let rule = Rule {
                 id,
                 condition,
                 action: action_type,
+                priority,
+                schedule,
             };
 
             processor.add_rule(rule).await?;
             info!("Rule added successfully");
         }
 
-        Commands::ListRules => {
+        Commands::Add { rule, id } => {
+            let parsed = match dsl::parse_rule(&rule, id.as_deref()) {
+                Ok(parsed) => parsed,
+                Err(e) => anyhow::bail!("{}", e.render(&rule)),
+            };
+            let rule_id = parsed.id.clone();
+            processor.add_rule(parsed).await?;
+            info!("Rule {} added successfully", rule_id);
+        }
+
+        Commands::ListRules { stats } => {
             let rules = processor.get_rules().await?;
+            let rule_stats: HashMap<String, RuleStats> = if stats {
+                processor
+                    .get_rule_stats()
+                    .await?
+                    .into_iter()
+                    .map(|s| (s.rule_id.clone(), s))
+                    .collect()
+            } else {
+                HashMap::new()
+            };
+
             if rules.is_empty() {
                 info!("No rules found");
             } else {
                 for rule in rules {
                     println!("Rule: {}", rule.id);
+                    println!("  DSL: {}", dsl::format_rule(&rule));
                     println!("  Condition: {:?}", rule.condition);
                     println!("  Action: {:?}", rule.action);
+                    println!("  Priority: {}", rule.priority);
+                    if let Some(schedule) = &rule.schedule {
+                        println!("  Schedule: {}", schedule);
+                    }
+                    println!("  Active: {}", if rule.is_active() { "yes" } else { "no" });
+                    if stats {
+                        let hits = rule_stats.get(&rule.id).cloned().unwrap_or_default();
+                        println!(
+                            "  Stats: evaluated {}, matched {}, filtered {}, flagged {}, modified {}",
+                            hits.evaluated, hits.matched, hits.filtered, hits.flagged, hits.modified
+                        );
+                        println!("  Attention saved: {}s", hits.time_saved / 1000);
+                        match hits.last_matched {
+                            Some(at) => println!("  Last matched: {}", at),
+                            None => println!("  Last matched: never"),
+                        }
+                    }
                     println!();
                 }
             }
         }
 
+        Commands::LintRules { models_dir } => {
+            let models_dir = models_dir.unwrap_or_else(|| db_path.with_file_name("models"));
+            let mut models = HashSet::new();
+            if let Ok(mut entries) = tokio::fs::read_dir(&models_dir).await {
+                while let Some(entry) = entries.next_entry().await? {
+                    if let Some(stem) = entry.path().file_stem() {
+                        models.insert(stem.to_string_lossy().into_owned());
+                    }
+                }
+            }
+
+            let issues = processor.lint_rules(&models).await?;
+            for issue in &issues {
+                println!("{}", issue);
+            }
+
+            let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+            println!("{} error(s), {} warning(s)", errors, issues.len() - errors);
+            if errors > 0 {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::TestRules { file } => {
+            let json = tokio::fs::read_to_string(&file).await?;
+            let suite = TestSuite::from_json(&json)?;
+            let results = processor.test_rules(&suite).await?;
+
+            for result in &results {
+                if result.passed() {
+                    println!("PASS {}", result.name);
+                } else {
+                    println!("FAIL {}", result.name);
+                    for failure in &result.failures {
+                        println!("  {}", failure);
+                    }
+                }
+            }
+
+            let failed = results.iter().filter(|r| !r.passed()).count();
+            println!("{} passed, {} failed", results.len() - failed, failed);
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        }
+
         Commands::Metrics { id, top } => {
             if let Some(content_id) = id {
                 if let Some(metrics) = processor.get_metrics(&content_id).await? {
@@ -196,6 +714,7 @@ async fn main() -> Result<()> {
                 view_duration: duration * 1000, // convert to milliseconds
                 metadata: HashMap::new(),
                 flags: vec![],
+                score: 0.0,
             };
 
             match processor.process_content(content).await? {
@@ -211,6 +730,53 @@ async fn main() -> Result<()> {
             }
         }
 
+        Commands::Rank { input } => {
+            let feed = tokio::fs::read_to_string(&input).await?;
+            let mut items = Vec::new();
+            for (number, line) in feed.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let item: Content = serde_json::from_str(line)
+                    .map_err(|e| anyhow::anyhow!("{}:{}: {}", input.display(), number + 1, e))?;
+                items.push(item);
+            }
+
+            let total = items.len();
+            let ranked = processor.rank(items).await?;
+            for item in &ranked {
+                println!("{}", serde_json::to_string(item)?);
+            }
+            info!("Ranked {} item(s), {} filtered out", ranked.len(), total - ranked.len());
+        }
+
+        Commands::Clickbait { text } => {
+            println!("{}", processor.score_clickbait(&text).await);
+        }
+
+        Commands::Digest { release, all } => {
+            let items = processor.get_digest(all).await?;
+            if items.is_empty() {
+                info!("Digest is empty");
+            } else {
+                for (flag, group) in group_by_flag(&items) {
+                    let flag = if flag.is_empty() { "(unflagged)" } else { flag };
+                    println!("== {} ({})", flag, group.len());
+                    for item in group {
+                        println!("  [{}] {}", item.content.id, item.content.text);
+                        println!("    queued {} by {}, due {}", item.queued_at, item.rule_id, item.release_at);
+                    }
+                    println!();
+                }
+
+                if release {
+                    let ids: Vec<String> = items.iter().map(|item| item.content.id.clone()).collect();
+                    processor.release_queued(&ids).await?;
+                    info!("Released {} item(s)", ids.len());
+                }
+            }
+        }
+
         Commands::Cleanup { days } => {
             processor.cleanup(days).await?;
             info!("Cleaned up metrics older than {} days", days);
@@ -227,6 +793,293 @@ async fn main() -> Result<()> {
             store.import_metrics(input).await?;
             info!("Metrics imported successfully");
         }
+
+        Commands::ImportList { file, format, id, flag } => {
+            let format: ListFormat = format.parse()?;
+            let list_id = match id {
+                Some(id) => id,
+                None => file
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .ok_or_else(|| anyhow::anyhow!("Cannot derive a list id from {}", file.display()))?,
+            };
+            let action = match flag {
+                Some(flag) => ActionType::Flag { flags: vec![flag] },
+                None => ActionType::Filter,
+            };
+
+            let input = tokio::fs::read_to_string(&file).await?;
+            let summary = processor
+                .import_list(&list_id, &file.display().to_string(), format, &input, action)
+                .await?;
+
+            println!("Imported list {}:", list_id);
+            println!("  Rules: {}", summary.imported);
+            println!("  Duplicates skipped: {}", summary.duplicates);
+            println!("  Removed since last import: {}", summary.removed);
+            println!("  Unsupported lines: {}", summary.skipped);
+        }
+
+        Commands::Lists => {
+            let lists = processor.get_blocklists().await?;
+            if lists.is_empty() {
+                info!("No lists imported");
+            } else {
+                for list in lists {
+                    println!("List: {}", list.id);
+                    println!("  Source: {} ({})", list.source, list.format);
+                    println!("  Rules: {}", list.rule_count);
+                    println!("  Imported: {}", list.imported_at);
+                    println!();
+                }
+            }
+        }
+
+        Commands::SyncRules { file } => {
+            let mut file = RuleFile::new(file)?;
+            let summary = processor.sync_rule_file(&mut file).await?;
+            println!("Synced {} rules from {}", summary.loaded, file.path().display());
+            if summary.removed > 0 {
+                println!("Removed {} rules no longer in the file", summary.removed);
+            }
+        }
+
+        Commands::Serve { rules_file, poll_interval } => {
+            let mut rule_file = rules_file.map(RuleFile::new).transpose()?;
+            if let Some(file) = rule_file.as_mut() {
+                let summary = processor.sync_rule_file(file).await?;
+                info!("Loaded {} rules from {}", summary.loaded, file.path().display());
+            }
+
+            let mut lines = BufReader::new(tokio::io::stdin()).lines();
+            let mut input_open = true;
+            let mut ticker = tokio::time::interval(Duration::from_secs(poll_interval.max(1)));
+
+            while input_open || rule_file.is_some() {
+                tokio::select! {
+                    line = lines.next_line(), if input_open => match line? {
+                        Some(line) if line.trim().is_empty() => {}
+                        Some(line) => match serde_json::from_str::<Content>(&line) {
+                            Ok(content) => {
+                                if let Some(processed) = processor.process_content(content).await? {
+                                    println!("{}", serde_json::to_string(&processed)?);
+                                }
+                            }
+                            Err(e) => error!("Invalid content: {}", e),
+                        },
+                        None => input_open = false,
+                    },
+                    _ = ticker.tick() => {
+                        let Some(file) = rule_file.as_mut() else { continue };
+                        match file.changed().await {
+                            Ok(false) => {}
+                            Ok(true) => match processor.sync_rule_file(file).await {
+                                Ok(summary) => info!("Reloaded {} rules from {}", summary.loaded, file.path().display()),
+                                Err(e) => error!("Keeping previous rules, {} is invalid: {:#}", file.path().display(), e),
+                            },
+                            Err(e) => error!("Cannot read {}: {}", file.path().display(), e),
+                        }
+                    }
+                    _ = tokio::signal::ctrl_c() => break,
+                }
+            }
+        }
+
+        Commands::Profile { command } => match command {
+            ProfileCommands::Create { name } => {
+                processor.create_profile(&name).await?;
+                info!("Profile {} created", name);
+            }
+            ProfileCommands::Delete { name } => {
+                processor.delete_profile(&name).await?;
+                info!("Profile {} deleted", name);
+            }
+            ProfileCommands::AddRule { profile, rule_id } => {
+                processor.add_rule_to_profile(&profile, &rule_id).await?;
+                info!("Rule {} added to profile {}", rule_id, profile);
+            }
+            ProfileCommands::RemoveRule { profile, rule_id } => {
+                processor.remove_rule_from_profile(&profile, &rule_id).await?;
+                info!("Rule {} removed from profile {}", rule_id, profile);
+            }
+            ProfileCommands::Use { name } => {
+                processor.use_profile(Some(&name)).await?;
+                info!("Switched to profile {}", name);
+            }
+            ProfileCommands::Clear => {
+                processor.use_profile(None).await?;
+                info!("Profile cleared, all rules active");
+            }
+            ProfileCommands::List => {
+                let profiles = processor.get_profiles().await?;
+                if profiles.is_empty() {
+                    info!("No profiles found");
+                } else {
+                    let active = processor.active_profile().await;
+                    for profile in profiles {
+                        let marker = if active.as_deref() == Some(profile.name.as_str()) { " (active)" } else { "" };
+                        println!("Profile: {}{}", profile.name, marker);
+                        println!("  Rules: {}", profile.rule_ids.join(", "));
+                        println!();
+                    }
+                }
+            }
+            ProfileCommands::Metrics => {
+                let metrics = processor.get_profile_metrics().await?;
+                if metrics.is_empty() {
+                    info!("No profile metrics recorded");
+                } else {
+                    for metric in metrics {
+                        println!("Profile: {}", metric.profile);
+                        println!("  Duration: {}ms", metric.total_duration);
+                        println!("  Interactions: {}", metric.interactions);
+                        println!("  Items: {}", metric.items);
+                        println!("  Last interaction: {}", metric.last_interaction);
+                        println!();
+                    }
+                }
+            }
+        },
+
+        Commands::Reputation { command } => match command {
+            ReputationCommands::List { key } => {
+                let reputations: Vec<_> = processor
+                    .get_reputations()
+                    .await?
+                    .into_iter()
+                    .filter(|r| key.as_ref().is_none_or(|key| &r.key == key))
+                    .collect();
+                if reputations.is_empty() {
+                    info!("No reputations recorded");
+                }
+                for reputation in reputations {
+                    let manual = if reputation.manual_score.is_some() { " (set by hand)" } else { "" };
+                    println!("{} {}: {:.2}{}", reputation.key, reputation.value, reputation.score(), manual);
+                    println!(
+                        "  Attention: {}ms, filtered: {}, flagged: {}, feedback: +{} -{}",
+                        reputation.attention_ms,
+                        reputation.filtered,
+                        reputation.flagged,
+                        reputation.likes,
+                        reputation.dislikes
+                    );
+                }
+            }
+            ReputationCommands::Set { key, value, score } => {
+                processor.set_reputation(&key, &value, Some(score)).await?;
+                info!("Reputation of {} {} set to {:.2}", key, value, score);
+            }
+            ReputationCommands::Reset { key, value, manual } => {
+                if manual {
+                    let value = value.unwrap_or_default();
+                    processor.set_reputation(&key, &value, None).await?;
+                    info!("Removed the manual score of {} {}", key, value);
+                } else {
+                    let removed = processor.reset_reputation(&key, value.as_deref()).await?;
+                    info!("Reset {} reputation(s)", removed);
+                }
+            }
+        },
+
+        Commands::Report { command } => match command {
+            ReportCommands::Sessions { idle_gap, days, json } => {
+                let since = chrono::Utc::now() - chrono::Duration::days(days);
+                let report = processor
+                    .get_session_report(since, chrono::Duration::minutes(idle_gap))
+                    .await?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else if report.sessions.is_empty() {
+                    info!("No attention recorded in the last {} day(s)", days);
+                } else {
+                    for session in &report.sessions {
+                        let fragmented = if session.is_fragmented() { " (fragmented)" } else { "" };
+                        println!(
+                            "{} - {}{}",
+                            session.start.format("%Y-%m-%d %H:%M"),
+                            session.end.format("%H:%M"),
+                            fragmented
+                        );
+                        println!(
+                            "  Length: {}s, attention: {}ms, items: {}, switches: {} ({:.1}/min)",
+                            session.length() / 1000,
+                            session.attention,
+                            session.items,
+                            session.switches,
+                            session.switching_rate()
+                        );
+                    }
+                    println!();
+                    println!("Sessions: {} (idle gap {} min)", report.sessions.len(), idle_gap);
+                    println!("  Average length: {:.0}s", report.average_length / 1000.0);
+                    println!("  Items per session: {:.1}", report.items_per_session);
+                    println!("  Switching rate: {:.1}/min", report.switching_rate);
+                    println!("  Time in fragmented sessions: {:.0}%", report.fragmented_share * 100.0);
+                }
+            }
+            ReportCommands::Heatmap { days, timezone, format } => {
+                let since = chrono::Utc::now() - chrono::Duration::days(days);
+                let heatmap = processor.get_heatmap(since, parse_timezone(&timezone)?).await?;
+                match format.as_str() {
+                    "ascii" => {
+                        println!("{}", heatmap.render());
+                        if let Some((day, hour, duration)) = heatmap.peak() {
+                            println!("Busiest hour: {} {:02}:00 ({}m)", day, hour, duration / 60_000);
+                        }
+                    }
+                    "json" => println!("{}", serde_json::to_string_pretty(&heatmap)?),
+                    "csv" => print!("{}", heatmap.to_csv()),
+                    other => anyhow::bail!("Unknown heatmap format '{}' (expected ascii, json or csv)", other),
+                }
+            }
+        },
+
+        Commands::Allow { command } => match command {
+            AllowCommands::Add { kind, pattern } => {
+                let entry = AllowEntry::new(kind, &pattern);
+                let id = entry.id();
+                processor.add_allow_entry(entry).await?;
+                info!("Added allowlist entry {}", id);
+            }
+            AllowCommands::Remove { kind, pattern } => {
+                let entry = AllowEntry::new(kind, &pattern);
+                if processor.remove_allow_entry(&entry).await? {
+                    info!("Removed allowlist entry {}", entry.id());
+                } else {
+                    error!("No allowlist entry {}", entry.id());
+                }
+            }
+            AllowCommands::List => {
+                let entries = processor.get_allowlist().await?;
+                if entries.is_empty() {
+                    info!("The allowlist is empty");
+                }
+                for entry in entries {
+                    println!("{}", entry.id());
+                }
+            }
+        },
+
+        Commands::Explain { id, text, meta, flag } => {
+            let mut metadata = HashMap::new();
+            for pair in meta {
+                let (key, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| anyhow::anyhow!("Metadata must look like key=value, got '{}'", pair))?;
+                metadata.insert(key.trim().to_string(), value.trim().to_string());
+            }
+            let content = Content {
+                id,
+                text,
+                view_duration: 0,
+                metadata,
+                flags: flag,
+                score: 0.0,
+            };
+            for line in processor.explain(content).await?.trace() {
+                println!("{}", line);
+            }
+        }
     }
 
     Ok(())
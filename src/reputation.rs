@@ -0,0 +1,206 @@
+use crate::content::{ConditionType, Content, Evaluation, Rule};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Metadata keys whose values get a reputation, e.g. `author` = `alice`
+pub const TRACKED_KEYS: &[&str] = &["author", "source"];
+
+/// Metadata key clients set to `up` or `down` to give feedback on an item
+pub const FEEDBACK_KEY: &str = "feedback";
+
+/// Score of a source nothing is known about
+pub const NEUTRAL_SCORE: f64 = 0.5;
+
+/// Weight of one like or dislike, in minutes of attention
+const FEEDBACK_WEIGHT: f64 = 3.0;
+
+/// Accumulated evidence about one author or source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reputation {
+    /// Metadata key, e.g. `author`
+    pub key: String,
+    /// Metadata value, e.g. `alice`
+    pub value: String,
+    /// Time spent on items from the source, in milliseconds
+    pub attention_ms: i64,
+    /// Items from the source that rules removed or held back
+    pub filtered: i64,
+    /// Items from the source that rules flagged
+    pub flagged: i64,
+    /// Items given `up` feedback
+    pub likes: i64,
+    /// Items given `down` feedback
+    pub dislikes: i64,
+    /// Score set by hand, replacing the computed one
+    pub manual_score: Option<f64>,
+    /// When the reputation last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Reputation {
+    /// Empty reputation with a neutral score
+    pub fn new(key: &str, value: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            value: value.to_string(),
+            attention_ms: 0,
+            filtered: 0,
+            flagged: 0,
+            likes: 0,
+            dislikes: 0,
+            manual_score: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Score between 0.0 (distrusted) and 1.0 (trusted), 0.5 without evidence
+    ///
+    /// Each minute of attention and each like counts in favour, each filtered item, half
+    /// of each flagged item and each dislike against; the ratio is smoothed so a single
+    /// event can't push the score to an extreme.
+    pub fn score(&self) -> f64 {
+        if let Some(score) = self.manual_score {
+            return score;
+        }
+        let positive = self.attention_ms as f64 / 60_000.0 + FEEDBACK_WEIGHT * self.likes as f64;
+        let negative = self.filtered as f64 + 0.5 * self.flagged as f64 + FEEDBACK_WEIGHT * self.dislikes as f64;
+        (positive + 1.0) / (positive + negative + 2.0)
+    }
+
+    /// Add an item's evidence
+    pub fn apply(&mut self, update: &ReputationUpdate, at: DateTime<Utc>) {
+        self.attention_ms += update.attention_ms;
+        self.filtered += update.filtered;
+        self.flagged += update.flagged;
+        self.likes += update.likes;
+        self.dislikes += update.dislikes;
+        self.updated_at = at;
+    }
+}
+
+/// Evidence one processed item adds to the reputation of its author and source
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReputationUpdate {
+    pub attention_ms: i64,
+    pub filtered: i64,
+    pub flagged: i64,
+    pub likes: i64,
+    pub dislikes: i64,
+}
+
+impl ReputationUpdate {
+    /// Evidence from how rules treated an item and the feedback it carries
+    ///
+    /// `matched` is the rule whose action was applied. If its condition looks at reputation,
+    /// filtering or flagging is not counted, so a low score can't keep pushing itself down.
+    pub fn from_evaluation(content: &Content, evaluation: &Evaluation, matched: Option<&Rule>) -> Self {
+        let circular = matched.is_some_and(depends_on_reputation);
+        let feedback = content.metadata.get(FEEDBACK_KEY).map(|f| f.to_lowercase());
+        let mut update = Self {
+            likes: matches!(feedback.as_deref(), Some("up" | "like" | "+1")) as i64,
+            dislikes: matches!(feedback.as_deref(), Some("down" | "dislike" | "-1")) as i64,
+            ..Self::default()
+        };
+        match &evaluation.result {
+            None => update.filtered = !circular as i64,
+            Some(processed) => {
+                update.attention_ms = processed.view_duration.max(0);
+                update.flagged = (!circular && processed.flags.len() > content.flags.len()) as i64;
+            }
+        }
+        update
+    }
+}
+
+/// Whether a rule's condition involves a reputation score
+pub fn depends_on_reputation(rule: &Rule) -> bool {
+    rule.condition
+        .walk()
+        .into_iter()
+        .any(|condition| matches!(condition, ConditionType::Reputation { .. }))
+}
+
+/// Tracked `(key, value)` pairs of an item, e.g. `("author", "alice")`
+pub fn sources_of(content: &Content) -> Vec<(&'static str, &str)> {
+    TRACKED_KEYS
+        .iter()
+        .filter_map(|key| {
+            let value = content.metadata.get(*key)?.trim();
+            (!value.is_empty()).then_some((*key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_score() {
+        let now = Utc::now();
+        let mut reputation = Reputation::new("author", "alice");
+        assert_eq!(reputation.score(), NEUTRAL_SCORE);
+
+        // Ten minutes of reading outweigh one filtered item
+        reputation.apply(&ReputationUpdate { attention_ms: 600_000, ..Default::default() }, now);
+        reputation.apply(&ReputationUpdate { filtered: 1, ..Default::default() }, now);
+        assert!(reputation.score() > 0.8);
+
+        let mut spammer = Reputation::new("source", "spam.example");
+        for _ in 0..5 {
+            spammer.apply(&ReputationUpdate { filtered: 1, dislikes: 1, ..Default::default() }, now);
+        }
+        assert!(spammer.score() < 0.1);
+
+        spammer.manual_score = Some(0.7);
+        assert_eq!(spammer.score(), 0.7);
+    }
+
+    #[test]
+    fn test_update_from_evaluation() {
+        let content = Content {
+            id: "test".to_string(),
+            text: "text".to_string(),
+            view_duration: 4_000,
+            metadata: HashMap::from([
+                ("author".to_string(), "alice".to_string()),
+                ("feedback".to_string(), "Up".to_string()),
+            ]),
+            flags: vec![],
+            score: 0.0,
+        };
+        let mut flagged = content.clone();
+        flagged.flags.push("rust".to_string());
+        let evaluation = Evaluation {
+            result: Some(flagged),
+            evaluated: vec![],
+            matched: None,
+            counted: false,
+            scored: vec![],
+            cleaned: vec![],
+            queued: None,
+            allowed: None,
+        };
+
+        let update = ReputationUpdate::from_evaluation(&content, &evaluation, None);
+        assert_eq!(update, ReputationUpdate { attention_ms: 4_000, flagged: 1, likes: 1, ..Default::default() });
+        assert_eq!(sources_of(&content), vec![("author", "alice")]);
+
+        // Matches of rules that look at reputation are no evidence against the source
+        let low_reputation = Rule {
+            id: "distrusted".to_string(),
+            condition: ConditionType::Reputation {
+                key: "author".to_string(),
+                op: crate::features::Comparison::Lt,
+                value: 0.3,
+            },
+            action: crate::content::ActionType::Filter,
+            priority: 0,
+            schedule: None,
+        };
+        let filtered = Evaluation { result: None, ..evaluation };
+        let update = ReputationUpdate::from_evaluation(&content, &filtered, Some(&low_reputation));
+        assert_eq!(update, ReputationUpdate { likes: 1, ..Default::default() });
+    }
+}
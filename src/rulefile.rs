@@ -0,0 +1,193 @@
+use crate::content::Rule;
+use crate::dsl;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Supported rule file formats, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleFileFormat {
+    /// `.toml`, with a top-level `[[rules]]` array
+    Toml,
+    /// `.yaml` / `.yml`, either a list of rules or a `rules:` key
+    Yaml,
+    /// `.json`, either an array of rules or a `rules` key
+    Json,
+    /// `.rules`, in the rule DSL
+    Dsl,
+}
+
+impl RuleFileFormat {
+    /// Detect the format from a path's extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml") | Some("yml") => Ok(Self::Yaml),
+            Some("json") => Ok(Self::Json),
+            Some("rules") => Ok(Self::Dsl),
+            _ => bail!("Unsupported rules file {}, expected .toml, .yaml, .json or .rules", path.display()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RuleTable {
+    rules: Vec<Rule>,
+}
+
+/// Parse and validate a rule set
+///
+/// Fails if any rule is invalid or two rules share an ID, so a broken file never
+/// replaces a working rule set.
+pub fn parse_rules(text: &str, format: RuleFileFormat) -> Result<Vec<Rule>> {
+    // Parse into the concrete shape so errors keep their line and column
+    let first = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .and_then(|line| line.chars().next());
+    let rules = match (format, first) {
+        (RuleFileFormat::Toml, _) => toml::from_str::<RuleTable>(text)?.rules,
+        // serde_yaml expects `!Tag` syntax for enums; going through JSON values accepts
+        // the same `Keyword: value` maps as the other formats
+        (RuleFileFormat::Yaml, Some('-')) => {
+            serde_json::from_value(serde_yaml::from_str::<serde_json::Value>(text)?)?
+        }
+        (RuleFileFormat::Yaml, _) => {
+            serde_json::from_value::<RuleTable>(serde_yaml::from_str::<serde_json::Value>(text)?)?.rules
+        }
+        (RuleFileFormat::Json, Some('[')) => serde_json::from_str(text)?,
+        (RuleFileFormat::Json, _) => serde_json::from_str::<RuleTable>(text)?.rules,
+        (RuleFileFormat::Dsl, _) => dsl::parse_rules(text)?,
+    };
+
+    let mut ids = HashSet::new();
+    for rule in &rules {
+        if !ids.insert(rule.id.as_str()) {
+            bail!("Duplicate rule id '{}'", rule.id);
+        }
+        rule.validate().with_context(|| format!("Invalid rule '{}'", rule.id))?;
+    }
+
+    Ok(rules)
+}
+
+/// Outcome of syncing a rules file into the store
+#[derive(Debug, Clone, Default)]
+pub struct SyncSummary {
+    /// Rules defined by the file
+    pub loaded: usize,
+    /// Rules from a previous sync that were removed from the file
+    pub removed: usize,
+}
+
+/// Rules file on disk, tracking its modification time to detect changes
+#[derive(Debug, Clone)]
+pub struct RuleFile {
+    path: PathBuf,
+    format: RuleFileFormat,
+    modified: Option<SystemTime>,
+}
+
+impl RuleFile {
+    /// Create a rule source for a file
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let format = RuleFileFormat::from_path(&path)?;
+        Ok(Self {
+            path,
+            format,
+            modified: None,
+        })
+    }
+
+    /// Path of the rules file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read, parse and validate the file, remembering its modification time
+    pub async fn load(&mut self) -> Result<Vec<Rule>> {
+        let modified = tokio::fs::metadata(&self.path).await?.modified().ok();
+        let text = tokio::fs::read_to_string(&self.path)
+            .await
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+
+        // Remember the attempt even if parsing fails, so a broken file is reported once
+        self.modified = modified;
+        parse_rules(&text, self.format)
+    }
+
+    /// Whether the file changed since it was last loaded
+    pub async fn changed(&self) -> Result<bool> {
+        let modified = tokio::fs::metadata(&self.path).await?.modified().ok();
+        Ok(modified.is_none() || modified != self.modified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{ActionType, ConditionType};
+
+    #[test]
+    fn test_parse_formats() {
+        let toml = r#"
+            [[rules]]
+            id = "spoilers"
+            condition = { Keyword = "ending" }
+            action = { Flag = { flags = ["spoiler"] } }
+            priority = 5
+
+            [[rules]]
+            id = "ads"
+            condition = { Regex = "(?i)sponsored" }
+            action = "Filter"
+        "#;
+        let yaml = "- id: ads\n  condition:\n    Keyword: sponsored\n  action: Filter\n";
+        let json = r#"{ "rules": [{ "id": "ads", "condition": { "Domain": "ads.example.com" }, "action": "Filter" }] }"#;
+
+        let rules = parse_rules(toml, RuleFileFormat::Toml).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].priority, 5);
+        assert!(matches!(&rules[0].action, ActionType::Flag { flags } if flags == &["spoiler"]));
+
+        let rules = parse_rules(yaml, RuleFileFormat::Yaml).unwrap();
+        assert!(matches!(&rules[0].condition, ConditionType::Keyword(k) if k == "sponsored"));
+
+        let rules = parse_rules(json, RuleFileFormat::Json).unwrap();
+        assert!(matches!(&rules[0].condition, ConditionType::Domain(d) if d == "ads.example.com"));
+
+        let rules = parse_rules("rule ads when domain = \"ads.example.com\" then filter", RuleFileFormat::Dsl).unwrap();
+        assert!(matches!(&rules[0].condition, ConditionType::Domain(d) if d == "ads.example.com"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_sets() {
+        let invalid_regex = r#"[{ "id": "a", "condition": { "Regex": "(" }, "action": "Filter" }]"#;
+        let duplicate_ids = r#"[
+            { "id": "a", "condition": { "Keyword": "x" }, "action": "Filter" },
+            { "id": "a", "condition": { "Keyword": "y" }, "action": "Filter" }
+        ]"#;
+
+        assert!(parse_rules(invalid_regex, RuleFileFormat::Json).is_err());
+        assert!(parse_rules(duplicate_ids, RuleFileFormat::Json).is_err());
+        assert!(RuleFileFormat::from_path(Path::new("rules.ini")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_change_detection() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("rules.json");
+        tokio::fs::write(&path, "[]").await?;
+
+        let mut file = RuleFile::new(&path)?;
+        assert!(file.changed().await?);
+        assert!(file.load().await?.is_empty());
+        assert!(!file.changed().await?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Daily time window in the schedule's timezone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeRange {
+    /// Start of the window (inclusive)
+    pub start: NaiveTime,
+    /// End of the window (exclusive); an end before the start wraps past midnight
+    pub end: NaiveTime,
+}
+
+impl TimeRange {
+    /// Whether the window crosses midnight
+    pub fn wraps(&self) -> bool {
+        self.end <= self.start
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = anyhow::Error;
+
+    /// Parse a range such as `09:00-17:00` or `22:00-06:00`
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((start, end)) = s.split_once('-') else {
+            bail!("Invalid time range '{}', expected HH:MM-HH:MM", s);
+        };
+        Ok(Self {
+            start: start.trim().parse()?,
+            end: end.trim().parse()?,
+        })
+    }
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+/// Schedule restricting when a rule is active
+///
+/// Named zones such as `Europe/Berlin` aren't supported. A fixed offset doesn't follow
+/// daylight saving time, so windows shift by an hour for part of the year wherever it
+/// applies; use `local` to follow the system timezone's clock changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Days of the week the rule applies on (empty means every day)
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Time windows the rule applies in (empty means all day)
+    #[serde(default)]
+    pub time_ranges: Vec<TimeRange>,
+    /// Timezone: `local` (default), `UTC` or a fixed offset such as `+02:00`, which
+    /// ignores daylight saving time
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// First date the rule is valid on (inclusive)
+    #[serde(default)]
+    pub valid_from: Option<NaiveDate>,
+    /// Last date the rule is valid on (inclusive)
+    #[serde(default)]
+    pub valid_until: Option<NaiveDate>,
+}
+
+impl Schedule {
+    /// Check the schedule for an unknown timezone or an empty validity period
+    pub fn validate(&self) -> Result<()> {
+        self.offset()?;
+        if let (Some(from), Some(until)) = (self.valid_from, self.valid_until) {
+            if until < from {
+                bail!("Schedule valid_until {} is before valid_from {}", until, from);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the schedule is active at the given instant
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        let Ok(local) = self.to_local(at) else {
+            return false;
+        };
+
+        if self.valid_from.is_some_and(|from| local.date() < from) {
+            return false;
+        }
+        if self.valid_until.is_some_and(|until| local.date() > until) {
+            return false;
+        }
+
+        if self.time_ranges.is_empty() {
+            return self.applies_on(local.weekday());
+        }
+
+        let time = local.time();
+        self.time_ranges.iter().any(|range| {
+            if !range.wraps() {
+                range.start <= time && time < range.end && self.applies_on(local.weekday())
+            } else if time >= range.start {
+                self.applies_on(local.weekday())
+            } else if time < range.end {
+                // The window opened the previous evening
+                self.applies_on((local - Duration::days(1)).weekday())
+            } else {
+                false
+            }
+        })
+    }
+
+    /// Whether the schedule is active right now
+    pub fn is_active(&self) -> bool {
+        self.is_active_at(Utc::now())
+    }
+
+    fn applies_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// Resolve the configured timezone; `None` means the system local time
+    fn offset(&self) -> Result<Option<FixedOffset>> {
+        parse_timezone(self.timezone.as_deref().unwrap_or("local"))
+    }
+
+    fn to_local(&self, at: DateTime<Utc>) -> Result<NaiveDateTime> {
+        Ok(to_local(at, self.offset()?))
+    }
+}
+
+/// Parse `local`, `UTC` or a fixed offset such as `+02:00`; `None` means the system local time
+pub fn parse_timezone(tz: &str) -> Result<Option<FixedOffset>> {
+    if tz.eq_ignore_ascii_case("local") {
+        Ok(None)
+    } else if tz.eq_ignore_ascii_case("utc") || tz.eq_ignore_ascii_case("z") {
+        Ok(FixedOffset::east_opt(0))
+    } else {
+        match tz.parse::<FixedOffset>() {
+            Ok(offset) => Ok(Some(offset)),
+            Err(_) => bail!("Invalid timezone '{}', expected local, UTC or +HH:MM (named zones aren't supported)", tz),
+        }
+    }
+}
+
+/// Wall-clock time of an instant at `offset`, or in the system local time if `None`
+pub fn to_local(at: DateTime<Utc>, offset: Option<FixedOffset>) -> NaiveDateTime {
+    match offset {
+        Some(offset) => at.with_timezone(&offset).naive_local(),
+        None => at.with_timezone(&Local).naive_local(),
+    }
+}
+
+/// Parse a comma-separated day list such as `mon,tue`, `weekdays` or `weekends`
+pub fn parse_days(s: &str) -> Result<Vec<Weekday>> {
+    let mut days: Vec<Weekday> = Vec::new();
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let parsed = match part.to_lowercase().as_str() {
+            "weekdays" => vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            "weekends" => vec![Weekday::Sat, Weekday::Sun],
+            other => match other.parse::<Weekday>() {
+                Ok(day) => vec![day],
+                Err(_) => bail!("Invalid day '{}'", part),
+            },
+        };
+        for day in parsed {
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+    }
+    Ok(days)
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days = if self.days.is_empty() {
+            "every day".to_string()
+        } else {
+            self.days.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(",")
+        };
+        let hours = if self.time_ranges.is_empty() {
+            "all day".to_string()
+        } else {
+            self.time_ranges.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(",")
+        };
+        write!(f, "{} {} ({})", days, hours, self.timezone.as_deref().unwrap_or("local"))?;
+        if let Some(from) = self.valid_from {
+            write!(f, " from {}", from)?;
+        }
+        if let Some(until) = self.valid_until {
+            write!(f, " until {}", until)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_weekday_business_hours() {
+        let schedule = Schedule {
+            days: parse_days("weekdays").unwrap(),
+            time_ranges: vec!["09:00-17:00".parse().unwrap()],
+            timezone: Some("UTC".to_string()),
+            ..Default::default()
+        };
+
+        // 2024-01-01 was a Monday
+        assert!(schedule.is_active_at(utc(2024, 1, 1, 10, 0)));
+        assert!(!schedule.is_active_at(utc(2024, 1, 1, 17, 0)));
+        assert!(!schedule.is_active_at(utc(2024, 1, 6, 10, 0)));
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let schedule = Schedule {
+            days: vec![Weekday::Fri],
+            time_ranges: vec!["22:00-06:00".parse().unwrap()],
+            timezone: Some("UTC".to_string()),
+            ..Default::default()
+        };
+
+        // Friday night into Saturday morning
+        assert!(schedule.is_active_at(utc(2024, 1, 5, 23, 0)));
+        assert!(schedule.is_active_at(utc(2024, 1, 6, 5, 0)));
+        assert!(!schedule.is_active_at(utc(2024, 1, 6, 23, 0)));
+        assert!(!schedule.is_active_at(utc(2024, 1, 5, 5, 0)));
+    }
+
+    #[test]
+    fn test_offset_and_validity() {
+        let schedule = Schedule {
+            time_ranges: vec!["22:00-00:00".parse().unwrap()],
+            timezone: Some("+02:00".to_string()),
+            valid_until: NaiveDate::from_ymd_opt(2024, 1, 31),
+            ..Default::default()
+        };
+
+        assert!(schedule.is_active_at(utc(2024, 1, 10, 20, 30)));
+        assert!(!schedule.is_active_at(utc(2024, 1, 10, 19, 30)));
+        assert!(!schedule.is_active_at(utc(2024, 2, 10, 20, 30)));
+    }
+
+    #[test]
+    fn test_invalid_timezone() {
+        let schedule = Schedule {
+            timezone: Some("Mars/Olympus".to_string()),
+            ..Default::default()
+        };
+
+        assert!(schedule.validate().is_err());
+        assert!(!schedule.is_active_at(Utc::now()));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::content::Content;
+use crate::lexicon::{Lexicon, LexiconScore};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Metadata key holding the sentiment score (-1.00 to 1.00)
+pub const SENTIMENT_KEY: &str = "sentiment";
+/// Metadata key holding the toxicity score (0.00 to 1.00)
+pub const TOXICITY_KEY: &str = "toxicity";
+
+/// Words carrying sentiment, with their valence
+const VALENCE: &[(&str, f64)] = &[
+    ("good", 1.0), ("great", 1.5), ("excellent", 2.0), ("amazing", 1.5), ("awesome", 1.5),
+    ("wonderful", 2.0), ("fantastic", 2.0), ("love", 1.5), ("loved", 1.5), ("like", 0.5),
+    ("happy", 1.5), ("glad", 1.0), ("nice", 1.0), ("best", 1.5), ("better", 1.0),
+    ("beautiful", 1.5), ("win", 1.0), ("wins", 1.0), ("success", 1.5), ("helpful", 1.0),
+    ("thanks", 1.0), ("thank", 1.0), ("enjoy", 1.0), ("enjoyed", 1.0), ("hope", 0.5),
+    ("safe", 0.5), ("calm", 0.5), ("fun", 1.0), ("kind", 1.0), ("celebrate", 1.5),
+    ("bad", -1.0), ("terrible", -2.0), ("awful", -2.0), ("horrible", -2.0), ("worst", -2.0),
+    ("worse", -1.0), ("hate", -2.0), ("hated", -2.0), ("sad", -1.5), ("angry", -1.5),
+    ("fear", -1.5), ("afraid", -1.0), ("scary", -1.0), ("crisis", -1.5), ("disaster", -2.0),
+    ("fail", -1.0), ("failed", -1.5), ("failure", -1.5), ("lose", -1.0), ("lost", -1.0),
+    ("death", -1.5), ("dead", -1.5), ("killed", -2.0), ("war", -1.5), ("attack", -1.5),
+    ("violence", -2.0), ("corrupt", -1.5), ("scandal", -1.5), ("broken", -1.0), ("wrong", -1.0),
+    ("pain", -1.5), ("hurt", -1.5), ("ugly", -1.5), ("boring", -1.0), ("annoying", -1.0),
+    ("furious", -2.0), ("disgusting", -2.0), ("shameful", -1.5), ("collapse", -1.5), ("threat", -1.5),
+    ("pathetic", -1.5), ("stupid", -1.5), ("idiot", -1.5), ("useless", -1.5), ("sick", -1.0),
+];
+
+/// Words that flip the valence of the next few words
+const NEGATORS: &[&str] = &["not", "no", "never", "nobody", "nothing", "neither", "nor", "without", "hardly"];
+
+/// Words that scale the valence of the next word
+const INTENSIFIERS: &[(&str, f64)] = &[
+    ("very", 1.5), ("really", 1.3), ("extremely", 1.8), ("so", 1.3), ("totally", 1.5),
+    ("absolutely", 1.6), ("slightly", 0.6), ("somewhat", 0.7), ("barely", 0.5),
+];
+
+/// Words after a negator that it still applies to
+const NEGATION_SCOPE: usize = 3;
+
+/// Built-in toxicity lexicon, in the same format as user lexicon files
+pub const TOXICITY_LEXICON: &str = r#"
+[[category]]
+name = "threat"
+weight = 0.8
+phrases = [
+    "kill you", "i will kill", "hope you die", "you should die", "hurt you", "watch your back",
+    "beat you up", "find where you live",
+]
+
+[[category]]
+name = "insult"
+weight = 0.4
+phrases = [
+    "idiot", "idiots", "moron", "morons", "stupid", "dumb", "loser", "losers", "pathetic",
+    "scum", "clown", "imbecile", "retard", "shut up", "worthless", "garbage person",
+]
+
+[[category]]
+name = "profanity"
+weight = 0.3
+phrases = [
+    "fuck", "fucking", "fucked", "shit", "bullshit", "crap", "damn", "bastard", "bitch",
+    "asshole", "dick", "piss off", "wtf",
+]
+patterns = [
+    # masked spellings such as f*ck or sh!t
+    '\bf[\*#@]+c?k(ing|ed)?\b',
+    '\bsh[\*#@!]+t\b',
+]
+"#;
+
+fn valences() -> &'static HashMap<&'static str, f64> {
+    static VALENCES: OnceLock<HashMap<&'static str, f64>> = OnceLock::new();
+    VALENCES.get_or_init(|| VALENCE.iter().copied().collect())
+}
+
+/// Sentiment of a text between -1.0 (negative) and 1.0 (positive)
+///
+/// Sums word valences, flipping words shortly after a negation and scaling words after
+/// an intensifier, then squashes the sum so longer texts don't saturate immediately.
+pub fn sentiment(text: &str) -> f64 {
+    let lowered = text.to_lowercase();
+    let words = lowered
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty());
+
+    let mut total = 0.0;
+    let mut negated_for = 0;
+    let mut scale = 1.0;
+    for word in words {
+        if NEGATORS.contains(&word) || word.ends_with("n't") {
+            negated_for = NEGATION_SCOPE;
+            continue;
+        }
+        if let Some((_, factor)) = INTENSIFIERS.iter().find(|(w, _)| *w == word) {
+            scale = *factor;
+            continue;
+        }
+        if let Some(valence) = valences().get(word) {
+            let sign = if negated_for > 0 { -0.5 } else { 1.0 };
+            total += valence * scale * sign;
+        }
+        scale = 1.0;
+        negated_for = negated_for.saturating_sub(1);
+    }
+
+    total / (total * total + 15.0).sqrt()
+}
+
+fn toxicity_lexicon() -> &'static Lexicon {
+    static TOXICITY: OnceLock<Lexicon> = OnceLock::new();
+    TOXICITY.get_or_init(|| Lexicon::from_toml(TOXICITY_LEXICON).expect("built-in toxicity lexicon is valid"))
+}
+
+/// Profanity/toxicity score of a text between 0.0 and 1.0, with the terms that produced it
+pub fn toxicity(text: &str) -> LexiconScore {
+    toxicity_lexicon().score(text)
+}
+
+/// Set the `sentiment` and `toxicity` metadata keys
+pub fn annotate(content: &mut Content) {
+    content
+        .metadata
+        .insert(SENTIMENT_KEY.to_string(), format!("{:.2}", sentiment(&content.text)));
+    content
+        .metadata
+        .insert(TOXICITY_KEY.to_string(), format!("{:.2}", toxicity(&content.text).score));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentiment() {
+        assert!(sentiment("What a wonderful, happy day. Thanks everyone!") > 0.5);
+        assert!(sentiment("Terrible disaster leaves city in crisis") < -0.5);
+        assert_eq!(sentiment("The meeting is on Tuesday"), 0.0);
+
+        // Negation flips and dampens, intensifiers scale
+        assert!(sentiment("not good") < 0.0);
+        assert!(sentiment("not bad") > 0.0);
+        assert!(sentiment("very good") > sentiment("good"));
+        assert!(sentiment("this isn't great") < 0.0);
+    }
+
+    #[test]
+    fn test_toxicity() {
+        let score = toxicity("Shut up, you pathetic idiot");
+        assert!((score.score - 1.0).abs() < 1e-9, "{}", score);
+        assert_eq!(score.hits.len(), 3);
+
+        assert_eq!(toxicity("What the f*ck").hits[0].category, "profanity");
+        assert_eq!(toxicity("I will kill you").hits[0].category, "threat");
+        assert_eq!(toxicity("Scunthorpe council publishes classic assessment").score, 0.0);
+    }
+}
@@ -1,7 +1,16 @@
-use anyhow::Result;
-use sqlx::{migrate::MigrateDatabase, sqlite::SqlitePool, Sqlite};
-use crate::{attention::Metrics, content::Rule};
+use anyhow::{Context, Result};
+use sqlx::{migrate::MigrateDatabase, sqlite::SqlitePool, Sqlite, Transaction};
+use crate::{
+    allowlist::AllowEntry,
+    attention::{AttentionEvent, Metrics, ProfileMetrics},
+    blocklist::{Blocklist, ListFormat},
+    content::{Profile, QueuedItem, Rule, RuleStats},
+    dedup::Seen,
+    reputation::{Reputation, ReputationUpdate},
+    schedule::Schedule,
+};
 use chrono::{DateTime, Utc};
+use std::collections::HashSet;
 use std::path::Path;
 
 /// Database operations for persistent storage
@@ -31,10 +40,128 @@ impl DataStore {
                 id TEXT PRIMARY KEY,
                 condition TEXT NOT NULL,
                 action TEXT NOT NULL,
+                priority INTEGER NOT NULL DEFAULT 0,
+                schedule TEXT,
                 created_at INTEGER NOT NULL,
                 updated_at INTEGER NOT NULL
             );
 
+            CREATE TABLE IF NOT EXISTS profiles (
+                name TEXT PRIMARY KEY NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS profile_rules (
+                profile TEXT NOT NULL,
+                rule_id TEXT NOT NULL,
+                PRIMARY KEY (profile, rule_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS profile_metrics (
+                profile TEXT NOT NULL,
+                content_id TEXT NOT NULL,
+                total_duration INTEGER NOT NULL,
+                interactions INTEGER NOT NULL,
+                last_interaction INTEGER NOT NULL,
+                PRIMARY KEY (profile, content_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY NOT NULL,
+                value TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS blocklists (
+                id TEXT PRIMARY KEY NOT NULL,
+                source TEXT NOT NULL,
+                format TEXT NOT NULL,
+                imported_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS blocklist_rules (
+                list_id TEXT NOT NULL,
+                rule_id TEXT NOT NULL,
+                PRIMARY KEY (list_id, rule_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS rule_file_rules (
+                path TEXT NOT NULL,
+                rule_id TEXT NOT NULL,
+                PRIMARY KEY (path, rule_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS rule_stats (
+                rule_id TEXT PRIMARY KEY NOT NULL,
+                evaluated INTEGER NOT NULL DEFAULT 0,
+                matched INTEGER NOT NULL DEFAULT 0,
+                filtered INTEGER NOT NULL DEFAULT 0,
+                flagged INTEGER NOT NULL DEFAULT 0,
+                modified INTEGER NOT NULL DEFAULT 0,
+                time_saved INTEGER NOT NULL DEFAULT 0,
+                last_matched INTEGER
+            );
+
+            CREATE TABLE IF NOT EXISTS fingerprints (
+                content_id TEXT PRIMARY KEY NOT NULL,
+                fingerprint INTEGER NOT NULL,
+                seen_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS quarantine (
+                content_id TEXT PRIMARY KEY NOT NULL,
+                rule_id TEXT NOT NULL,
+                content TEXT NOT NULL,
+                queued_at INTEGER NOT NULL,
+                release_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS allowlist (
+                kind TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                added_at INTEGER NOT NULL,
+                PRIMARY KEY (kind, pattern)
+            );
+
+            CREATE TABLE IF NOT EXISTS reputation (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                attention_ms INTEGER NOT NULL DEFAULT 0,
+                filtered INTEGER NOT NULL DEFAULT 0,
+                flagged INTEGER NOT NULL DEFAULT 0,
+                likes INTEGER NOT NULL DEFAULT 0,
+                dislikes INTEGER NOT NULL DEFAULT 0,
+                manual_score REAL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (key, value)
+            );
+
+            CREATE TABLE IF NOT EXISTS reputation_items (
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                content_id TEXT NOT NULL,
+                PRIMARY KEY (key, value, content_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS attention_events (
+                content_id TEXT NOT NULL,
+                duration INTEGER NOT NULL,
+                at INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_attention_events_at
+            ON attention_events(at);
+
+            CREATE TABLE IF NOT EXISTS rate_limit_hits (
+                rule_id TEXT NOT NULL,
+                hit_at INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_rate_limit_hits_rule
+            ON rate_limit_hits(rule_id, hit_at);
+
+            CREATE INDEX IF NOT EXISTS idx_fingerprints_seen_at
+            ON fingerprints(seen_at);
+
             CREATE INDEX IF NOT EXISTS idx_metrics_last_interaction 
             ON metrics(last_interaction);
             
@@ -45,6 +172,25 @@ impl DataStore {
         .execute(&self.pool)
         .await?;
 
+        // Databases created by older versions lack columns added to existing tables since
+        self.add_column_if_missing("rules", "priority", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("rules", "schedule", "TEXT").await?;
+
+        Ok(())
+    }
+
+    async fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+            .bind(table)
+            .bind(column)
+            .fetch_one(&self.pool)
+            .await?;
+        if exists == 0 {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                .execute(&self.pool)
+                .await?;
+        }
+
         Ok(())
     }
 
@@ -130,13 +276,15 @@ impl DataStore {
         sqlx::query(
             r#"
             INSERT OR REPLACE INTO rules 
-            (id, condition, action, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?)
+            (id, condition, action, priority, schedule, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&rule.id)
         .bind(serde_json::to_string(&rule.condition)?)
         .bind(serde_json::to_string(&rule.action)?)
+        .bind(rule.priority)
+        .bind(rule.schedule.as_ref().map(serde_json::to_string).transpose()?)
         .bind(now)
         .bind(now)
         .execute(&self.pool)
@@ -149,41 +297,923 @@ impl DataStore {
     pub async fn get_rule(&self, rule_id: &str) -> Result<Option<Rule>> {
         let record = sqlx::query!(
             r#"
-            SELECT * FROM rules WHERE id = ?
+            SELECT id, condition, action, priority, schedule FROM rules WHERE id = ?
             "#,
             rule_id
         )
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(record.map(|r| Rule {
-            id: r.id,
-            condition: serde_json::from_str(&r.condition).unwrap(),
-            action: serde_json::from_str(&r.action).unwrap(),
-        }))
+        record
+            .map(|r| {
+                Ok(Rule {
+                    schedule: parse_schedule(&r.id, r.schedule)?,
+                    id: r.id,
+                    condition: serde_json::from_str(&r.condition).unwrap(),
+                    action: serde_json::from_str(&r.action).unwrap(),
+                    priority: r.priority as i32,
+                })
+            })
+            .transpose()
     }
 
     /// Get all rules
     pub async fn get_all_rules(&self) -> Result<Vec<Rule>> {
         let records = sqlx::query!(
             r#"
-            SELECT * FROM rules 
+            SELECT id, condition, action, priority, schedule FROM rules
             ORDER BY updated_at DESC
             "#
         )
         .fetch_all(&self.pool)
         .await?;
 
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(Rule {
+                    schedule: parse_schedule(&r.id, r.schedule)?,
+                    id: r.id,
+                    condition: serde_json::from_str(&r.condition).unwrap(),
+                    action: serde_json::from_str(&r.action).unwrap(),
+                    priority: r.priority as i32,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete a rule and its profile and blocklist assignments
+    pub async fn delete_rule(&self, rule_id: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        Self::delete_rule_in(&mut tx, rule_id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_rule_in(tx: &mut Transaction<'_, Sqlite>, rule_id: &str) -> Result<()> {
+        for sql in [
+            "DELETE FROM rules WHERE id = ?",
+            "DELETE FROM profile_rules WHERE rule_id = ?",
+            "DELETE FROM blocklist_rules WHERE rule_id = ?",
+            "DELETE FROM rule_file_rules WHERE rule_id = ?",
+            "DELETE FROM rule_stats WHERE rule_id = ?",
+            "DELETE FROM rate_limit_hits WHERE rule_id = ?",
+        ] {
+            sqlx::query(sql).bind(rule_id).execute(&mut **tx).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a profile if it doesn't exist
+    pub async fn create_profile(&self, name: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO profiles (name, created_at)
+            VALUES (?, ?)
+            "#,
+        )
+        .bind(name)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delete a profile and its rule assignments, keeping its metrics
+    pub async fn delete_profile(&self, name: &str) -> Result<()> {
+        sqlx::query("DELETE FROM profile_rules WHERE profile = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM profiles WHERE name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Check whether a profile exists
+    pub async fn profile_exists(&self, name: &str) -> Result<bool> {
+        let record = sqlx::query!(
+            r#"
+            SELECT name FROM profiles WHERE name = ?
+            "#,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record.is_some())
+    }
+
+    /// Assign a rule to a profile
+    pub async fn add_rule_to_profile(&self, profile: &str, rule_id: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO profile_rules (profile, rule_id)
+            VALUES (?, ?)
+            "#,
+        )
+        .bind(profile)
+        .bind(rule_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove a rule from a profile
+    pub async fn remove_rule_from_profile(&self, profile: &str, rule_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM profile_rules WHERE profile = ? AND rule_id = ?")
+            .bind(profile)
+            .bind(rule_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get all profiles with their rule IDs
+    pub async fn get_profiles(&self) -> Result<Vec<Profile>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT p.name, pr.rule_id
+            FROM profiles p
+            LEFT JOIN profile_rules pr ON pr.profile = p.name
+            ORDER BY p.name, pr.rule_id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut profiles: Vec<Profile> = Vec::new();
+        for row in records {
+            if profiles.last().map(|p| &p.name) != Some(&row.name) {
+                profiles.push(Profile {
+                    name: row.name.clone(),
+                    rule_ids: Vec::new(),
+                });
+            }
+            if let (Some(profile), Some(rule_id)) = (profiles.last_mut(), row.rule_id) {
+                profile.rule_ids.push(rule_id);
+            }
+        }
+
+        Ok(profiles)
+    }
+
+    /// Get the rules assigned to a profile
+    pub async fn get_profile_rules(&self, profile: &str) -> Result<Vec<Rule>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT r.id as "id!", r.condition, r.action, r.priority, r.schedule
+            FROM rules r
+            JOIN profile_rules pr ON pr.rule_id = r.id
+            WHERE pr.profile = ?
+            ORDER BY r.updated_at DESC
+            "#,
+            profile
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|row| {
+                Ok(Rule {
+                    schedule: parse_schedule(&row.id, row.schedule)?,
+                    condition: serde_json::from_str(&row.condition)?,
+                    action: serde_json::from_str(&row.action)?,
+                    id: row.id,
+                    priority: row.priority as i32,
+                })
+            })
+            .collect()
+    }
+
+    /// Persist the active profile, or clear it with `None`
+    pub async fn set_active_profile(&self, profile: Option<&str>) -> Result<()> {
+        match profile {
+            Some(name) => {
+                sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('active_profile', ?)")
+                    .bind(name)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM settings WHERE key = 'active_profile'")
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the active profile, if any
+    pub async fn get_active_profile(&self) -> Result<Option<String>> {
+        let record = sqlx::query!(
+            r#"
+            SELECT value FROM settings WHERE key = 'active_profile'
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record.map(|row| row.value))
+    }
+
+    /// Add attention time for content seen under a profile
+    pub async fn record_profile_attention(&self, profile: &str, content_id: &str, duration: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO profile_metrics
+            (profile, content_id, total_duration, interactions, last_interaction)
+            VALUES (?, ?, ?, 1, ?)
+            ON CONFLICT (profile, content_id) DO UPDATE SET
+                total_duration = total_duration + excluded.total_duration,
+                interactions = interactions + 1,
+                last_interaction = excluded.last_interaction
+            "#,
+        )
+        .bind(profile)
+        .bind(content_id)
+        .bind(duration)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get attention metrics aggregated per profile
+    pub async fn get_profile_metrics(&self) -> Result<Vec<ProfileMetrics>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                profile as "profile!",
+                SUM(total_duration) as "total_duration!: i64",
+                SUM(interactions) as "interactions!: i64",
+                COUNT(*) as "items!: i64",
+                MAX(last_interaction) as "last_interaction!: i64"
+            FROM profile_metrics
+            GROUP BY profile
+            ORDER BY 2 DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|row| ProfileMetrics {
+                profile: row.profile,
+                total_duration: row.total_duration,
+                interactions: row.interactions,
+                items: row.items,
+                last_interaction: DateTime::from_timestamp(row.last_interaction, 0)
+                    .unwrap_or_else(Utc::now),
+            })
+            .collect())
+    }
+
+    /// Average attention per interaction across all content, in milliseconds
+    pub async fn get_average_duration(&self) -> Result<Option<f64>> {
+        let record = sqlx::query!(
+            r#"
+            SELECT SUM(total_duration) as "total: i64", SUM(interactions) as "interactions: i64"
+            FROM metrics
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(match (record.total, record.interactions) {
+            (Some(total), Some(interactions)) if interactions > 0 => Some(total as f64 / interactions as f64),
+            _ => None,
+        })
+    }
+
+    /// Count one evaluation for each of the given rules
+    pub async fn record_rule_evaluations(&self, rule_ids: &[String]) -> Result<()> {
+        if rule_ids.is_empty() {
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO rule_stats (rule_id, evaluated)
+            SELECT value, 1 FROM json_each(?) WHERE true
+            ON CONFLICT (rule_id) DO UPDATE SET evaluated = evaluated + 1
+            "#,
+        )
+        .bind(serde_json::to_string(rule_ids)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Count a match for a rule and what its action did
+    pub async fn record_rule_match(
+        &self,
+        rule_id: &str,
+        filtered: bool,
+        flagged: bool,
+        modified: bool,
+        time_saved: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO rule_stats (rule_id, matched, filtered, flagged, modified, time_saved, last_matched)
+            VALUES (?, 1, ?, ?, ?, ?, ?)
+            ON CONFLICT (rule_id) DO UPDATE SET
+                matched = matched + 1,
+                filtered = filtered + excluded.filtered,
+                flagged = flagged + excluded.flagged,
+                modified = modified + excluded.modified,
+                time_saved = time_saved + excluded.time_saved,
+                last_matched = excluded.last_matched
+            "#,
+        )
+        .bind(rule_id)
+        .bind(filtered as i64)
+        .bind(flagged as i64)
+        .bind(modified as i64)
+        .bind(time_saved)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get hit statistics for all rules
+    pub async fn get_rule_stats(&self) -> Result<Vec<RuleStats>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT rule_id, evaluated, matched, filtered, flagged, modified, time_saved, last_matched
+            FROM rule_stats
+            ORDER BY matched DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|row| RuleStats {
+                rule_id: row.rule_id,
+                evaluated: row.evaluated,
+                matched: row.matched,
+                filtered: row.filtered,
+                flagged: row.flagged,
+                modified: row.modified,
+                time_saved: row.time_saved,
+                last_matched: row.last_matched.and_then(|t| DateTime::from_timestamp(t, 0)),
+            })
+            .collect())
+    }
+
+    /// Add content to the digest queue
+    ///
+    /// Content that is already queued keeps its release time, so re-polled items still
+    /// come due; only the stored copy is updated.
+    pub async fn queue_content(&self, item: &QueuedItem) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO quarantine (content_id, rule_id, content, queued_at, release_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(content_id) DO UPDATE SET
+                rule_id = excluded.rule_id,
+                content = excluded.content
+            "#,
+        )
+        .bind(&item.content.id)
+        .bind(&item.rule_id)
+        .bind(serde_json::to_string(&item.content)?)
+        .bind(item.queued_at.timestamp())
+        .bind(item.release_at.timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get queued content due by `due`, or all queued content for `None`, oldest first
+    pub async fn get_queue(&self, due: Option<DateTime<Utc>>) -> Result<Vec<QueuedItem>> {
+        let due = due.map_or(i64::MAX, |due| due.timestamp());
+        let records = sqlx::query!(
+            r#"
+            SELECT rule_id, content, queued_at, release_at
+            FROM quarantine
+            WHERE release_at <= ?
+            ORDER BY queued_at
+            "#,
+            due
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|row| {
+                Ok(QueuedItem {
+                    content: serde_json::from_str(&row.content)?,
+                    rule_id: row.rule_id,
+                    queued_at: DateTime::from_timestamp(row.queued_at, 0).unwrap_or_default(),
+                    release_at: DateTime::from_timestamp(row.release_at, 0).unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Remove content from the digest queue
+    pub async fn release_queued(&self, content_ids: &[String]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for content_id in content_ids {
+            sqlx::query("DELETE FROM quarantine WHERE content_id = ?")
+                .bind(content_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Record one stretch of attention on a content item
+    pub async fn record_attention_event(&self, event: &AttentionEvent) -> Result<()> {
+        sqlx::query("INSERT INTO attention_events (content_id, duration, at) VALUES (?, ?, ?)")
+            .bind(&event.content_id)
+            .bind(event.duration)
+            .bind(event.at.timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get attention events that ended at or after `since`, oldest first
+    pub async fn get_attention_events(&self, since: DateTime<Utc>) -> Result<Vec<AttentionEvent>> {
+        let since = since.timestamp();
+        let records = sqlx::query!(
+            r#"
+            SELECT content_id, duration, at
+            FROM attention_events
+            WHERE at >= ?
+            ORDER BY at
+            "#,
+            since
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|row| AttentionEvent {
+                content_id: row.content_id,
+                duration: row.duration,
+                at: DateTime::from_timestamp(row.at, 0).unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Record a match a rate-limit rule let through
+    pub async fn record_rate_limit_hit(&self, rule_id: &str, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("INSERT INTO rate_limit_hits (rule_id, hit_at) VALUES (?, ?)")
+            .bind(rule_id)
+            .bind(at.timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get rate-limit hits since `since`, as `(rule_id, time)` pairs
+    pub async fn get_rate_limit_hits(&self, since: DateTime<Utc>) -> Result<Vec<(String, DateTime<Utc>)>> {
+        let since = since.timestamp();
+        let records = sqlx::query!(
+            r#"
+            SELECT rule_id, hit_at
+            FROM rate_limit_hits
+            WHERE hit_at >= ?
+            ORDER BY hit_at
+            "#,
+            since
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|row| (row.rule_id, DateTime::from_timestamp(row.hit_at, 0).unwrap_or_default()))
+            .collect())
+    }
+
+    /// Delete rate-limit hits older than `before`
+    pub async fn prune_rate_limit_hits(&self, before: DateTime<Utc>) -> Result<()> {
+        sqlx::query("DELETE FROM rate_limit_hits WHERE hit_at < ?")
+            .bind(before.timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record the fingerprint of shown content, keeping the first sighting
+    pub async fn record_fingerprint(&self, seen: &Seen) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO fingerprints (content_id, fingerprint, seen_at)
+            VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(&seen.content_id)
+        // SQLite integers are signed; store the bits unchanged
+        .bind(seen.fingerprint as i64)
+        .bind(seen.seen_at.timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get all recorded fingerprints, oldest first
+    pub async fn get_fingerprints(&self) -> Result<Vec<Seen>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT content_id, fingerprint, seen_at
+            FROM fingerprints
+            ORDER BY seen_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|row| Seen {
+                content_id: row.content_id,
+                fingerprint: row.fingerprint as u64,
+                seen_at: DateTime::from_timestamp(row.seen_at, 0).unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Add an allowlist entry; adding an existing entry does nothing
+    pub async fn add_allow_entry(&self, entry: &AllowEntry) -> Result<()> {
+        sqlx::query("INSERT OR IGNORE INTO allowlist (kind, pattern, added_at) VALUES (?, ?, ?)")
+            .bind(entry.kind.name())
+            .bind(&entry.pattern)
+            .bind(Utc::now().timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove an allowlist entry, returning whether it existed
+    pub async fn remove_allow_entry(&self, entry: &AllowEntry) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM allowlist WHERE kind = ? AND pattern = ?")
+            .bind(entry.kind.name())
+            .bind(&entry.pattern)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Get all allowlist entries, oldest first
+    pub async fn get_allowlist(&self) -> Result<Vec<AllowEntry>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT kind, pattern
+            FROM allowlist
+            ORDER BY added_at, kind, pattern
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|row| Ok(AllowEntry::new(row.kind.parse()?, &row.pattern)))
+            .collect()
+    }
+
+    /// Add an item's evidence to a source's reputation and return the updated reputation
+    ///
+    /// Each content item counts once per source; returns `None` for an item that was
+    /// already counted.
+    pub async fn record_reputation(
+        &self,
+        key: &str,
+        value: &str,
+        content_id: &str,
+        update: &ReputationUpdate,
+        at: DateTime<Utc>,
+    ) -> Result<Option<Reputation>> {
+        let mut tx = self.pool.begin().await?;
+        let counted = sqlx::query("INSERT OR IGNORE INTO reputation_items (key, value, content_id) VALUES (?, ?, ?)")
+            .bind(key)
+            .bind(value)
+            .bind(content_id)
+            .execute(&mut *tx)
+            .await?;
+        if counted.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO reputation (key, value, attention_ms, filtered, flagged, likes, dislikes, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (key, value) DO UPDATE SET
+                attention_ms = attention_ms + excluded.attention_ms,
+                filtered = filtered + excluded.filtered,
+                flagged = flagged + excluded.flagged,
+                likes = likes + excluded.likes,
+                dislikes = dislikes + excluded.dislikes,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .bind(update.attention_ms)
+        .bind(update.filtered)
+        .bind(update.flagged)
+        .bind(update.likes)
+        .bind(update.dislikes)
+        .bind(at.timestamp())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.get_reputation(key, value)
+            .await?
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("Reputation for {} '{}' was not saved", key, value))
+    }
+
+    /// Get the reputation of one source
+    pub async fn get_reputation(&self, key: &str, value: &str) -> Result<Option<Reputation>> {
+        let record = sqlx::query!(
+            r#"
+            SELECT key, value, attention_ms, filtered, flagged, likes, dislikes, manual_score, updated_at
+            FROM reputation
+            WHERE key = ? AND value = ?
+            "#,
+            key,
+            value
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record.map(|row| Reputation {
+            key: row.key,
+            value: row.value,
+            attention_ms: row.attention_ms,
+            filtered: row.filtered,
+            flagged: row.flagged,
+            likes: row.likes,
+            dislikes: row.dislikes,
+            manual_score: row.manual_score,
+            updated_at: DateTime::from_timestamp(row.updated_at, 0).unwrap_or_default(),
+        }))
+    }
+
+    /// Get all reputations
+    pub async fn get_reputations(&self) -> Result<Vec<Reputation>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT key, value, attention_ms, filtered, flagged, likes, dislikes, manual_score, updated_at
+            FROM reputation
+            ORDER BY key, value
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
         Ok(records
             .into_iter()
-            .map(|r| Rule {
-                id: r.id,
-                condition: serde_json::from_str(&r.condition).unwrap(),
-                action: serde_json::from_str(&r.action).unwrap(),
+            .map(|row| Reputation {
+                key: row.key,
+                value: row.value,
+                attention_ms: row.attention_ms,
+                filtered: row.filtered,
+                flagged: row.flagged,
+                likes: row.likes,
+                dislikes: row.dislikes,
+                manual_score: row.manual_score,
+                updated_at: DateTime::from_timestamp(row.updated_at, 0).unwrap_or_default(),
             })
             .collect())
     }
 
+    /// Set a source's score by hand, or return it to the computed score with `None`
+    pub async fn set_reputation(&self, key: &str, value: &str, score: Option<f64>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO reputation (key, value, manual_score, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (key, value) DO UPDATE SET
+                manual_score = excluded.manual_score,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .bind(score)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Forget the reputation of one source, or of every source under `key` when `value` is `None`
+    ///
+    /// Returns how many reputations were removed.
+    pub async fn reset_reputation(&self, key: &str, value: Option<&str>) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut removed = 0;
+        for table in ["reputation", "reputation_items"] {
+            let result = match value {
+                Some(value) => {
+                    sqlx::query(&format!("DELETE FROM {} WHERE key = ? AND value = ?", table))
+                        .bind(key)
+                        .bind(value)
+                        .execute(&mut *tx)
+                        .await?
+                }
+                None => {
+                    sqlx::query(&format!("DELETE FROM {} WHERE key = ?", table))
+                        .bind(key)
+                        .execute(&mut *tx)
+                        .await?
+                }
+            };
+            if table == "reputation" {
+                removed = result.rows_affected();
+            }
+        }
+        tx.commit().await?;
+
+        Ok(removed)
+    }
+
+    /// Get the IDs of the rules owned by a blocklist
+    pub async fn get_blocklist_rule_ids(&self, list_id: &str) -> Result<Vec<String>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT rule_id FROM blocklist_rules WHERE list_id = ?
+            "#,
+            list_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records.into_iter().map(|row| row.rule_id).collect())
+    }
+
+    /// Replace the rules owned by a blocklist in a single transaction
+    ///
+    /// Rules from a previous import that are not in `rules` are deleted; returns how many.
+    pub async fn replace_blocklist(
+        &self,
+        list_id: &str,
+        source: &str,
+        format: ListFormat,
+        rules: &[Rule],
+    ) -> Result<usize> {
+        let previous = self.get_blocklist_rule_ids(list_id).await?;
+        let mut tx = self.pool.begin().await?;
+
+        let removed =
+            Self::replace_owned_rules(&mut tx, "blocklist_rules", "list_id", list_id, &previous, rules).await?;
+
+        sqlx::query("INSERT OR REPLACE INTO blocklists (id, source, format, imported_at) VALUES (?, ?, ?, ?)")
+            .bind(list_id)
+            .bind(source)
+            .bind(format.to_string())
+            .bind(Utc::now().timestamp())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    /// Get the IDs of the rules synced from a rules file
+    pub async fn get_rule_file_rule_ids(&self, path: &str) -> Result<Vec<String>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT rule_id FROM rule_file_rules WHERE path = ?
+            "#,
+            path
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records.into_iter().map(|row| row.rule_id).collect())
+    }
+
+    /// Replace the rules synced from a rules file in a single transaction
+    ///
+    /// Rules from a previous sync that are no longer in the file are deleted; returns how many.
+    pub async fn replace_rule_file(&self, path: &str, rules: &[Rule]) -> Result<usize> {
+        let previous = self.get_rule_file_rule_ids(path).await?;
+        let mut tx = self.pool.begin().await?;
+
+        let removed = Self::replace_owned_rules(&mut tx, "rule_file_rules", "path", path, &previous, rules).await?;
+
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    /// Upsert `rules` and link them to `owner` in an ownership table, deleting previously
+    /// owned rules that are no longer present
+    async fn replace_owned_rules(
+        tx: &mut Transaction<'_, Sqlite>,
+        table: &str,
+        owner_column: &str,
+        owner: &str,
+        previous: &[String],
+        rules: &[Rule],
+    ) -> Result<usize> {
+        let now = Utc::now().timestamp();
+
+        let keep: HashSet<&str> = rules.iter().map(|r| r.id.as_str()).collect();
+        let mut removed = 0;
+        for rule_id in previous.iter().filter(|id| !keep.contains(id.as_str())) {
+            Self::delete_rule_in(tx, rule_id).await?;
+            removed += 1;
+        }
+
+        let link = format!("INSERT OR IGNORE INTO {} ({}, rule_id) VALUES (?, ?)", table, owner_column);
+        for rule in rules {
+            sqlx::query(
+                r#"
+                INSERT INTO rules (id, condition, action, priority, schedule, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT (id) DO UPDATE SET
+                    condition = excluded.condition,
+                    action = excluded.action,
+                    priority = excluded.priority,
+                    schedule = excluded.schedule,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(&rule.id)
+            .bind(serde_json::to_string(&rule.condition)?)
+            .bind(serde_json::to_string(&rule.action)?)
+            .bind(rule.priority)
+            .bind(rule.schedule.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(now)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+
+            sqlx::query(&link).bind(owner).bind(&rule.id).execute(&mut **tx).await?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Get all imported blocklists
+    pub async fn get_blocklists(&self) -> Result<Vec<Blocklist>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT b.id, b.source, b.format, b.imported_at, COUNT(br.rule_id) as "rule_count!: i64"
+            FROM blocklists b
+            LEFT JOIN blocklist_rules br ON br.list_id = b.id
+            GROUP BY b.id
+            ORDER BY b.id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|row| {
+                Ok(Blocklist {
+                    id: row.id,
+                    source: row.source,
+                    format: row.format.parse()?,
+                    rule_count: row.rule_count,
+                    imported_at: DateTime::from_timestamp(row.imported_at, 0)
+                        .unwrap_or_else(Utc::now),
+                })
+            })
+            .collect()
+    }
+
     /// Clean up old metrics
     pub async fn cleanup(&self, days_to_keep: i64) -> Result<()> {
         let cutoff = Utc::now().timestamp() - (days_to_keep * 24 * 60 * 60);
@@ -198,6 +1228,16 @@ impl DataStore {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query("DELETE FROM fingerprints WHERE seen_at < ?")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query("DELETE FROM attention_events WHERE at < ?")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+
         Ok(())
     }
 
@@ -222,12 +1262,20 @@ impl DataStore {
     }
 }
 
+/// Decode a rule's stored schedule
+fn parse_schedule(rule_id: &str, schedule: Option<String>) -> Result<Option<Schedule>> {
+    schedule
+        .map(|s| serde_json::from_str(&s).with_context(|| format!("Invalid schedule stored for rule '{}'", rule_id)))
+        .transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::tempdir;
+    use tempfile::{tempdir, TempDir};
 
-    async fn setup_test_db() -> Result<(SqlitePool, DataStore)> {
+    /// The database lives in the returned directory, which is deleted once dropped
+    async fn setup_test_db() -> Result<(TempDir, SqlitePool, DataStore)> {
         let dir = tempdir()?;
         let db_path = dir.path().join("test.db");
         let database_url = format!("sqlite:{}", db_path.display());
@@ -237,12 +1285,12 @@ mod tests {
         let store = DataStore::new(pool.clone());
         store.initialize().await?;
         
-        Ok((pool, store))
+        Ok((dir, pool, store))
     }
 
     #[tokio::test]
     async fn test_metrics_crud() -> Result<()> {
-        let (_pool, store) = setup_test_db().await?;
+        let (_dir, _pool, store) = setup_test_db().await?;
         
         let metrics = Metrics {
             content_id: "test".to_string(),
@@ -265,7 +1313,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_rules_crud() -> Result<()> {
-        let (_pool, store) = setup_test_db().await?;
+        let (_dir, pool, store) = setup_test_db().await?;
         
         use crate::content::{ConditionType, ActionType};
 
@@ -273,6 +1321,8 @@ mod tests {
             id: "test".to_string(),
             condition: ConditionType::Keyword("test".to_string()),
             action: ActionType::Filter,
+            priority: 0,
+            schedule: None,
         };
 
         // Create
@@ -282,6 +1332,287 @@ mod tests {
         let saved = store.get_rule(&rule.id).await?.unwrap();
         assert_eq!(saved.id, rule.id);
 
+        // A hand-edited schedule is an error, not a panic
+        sqlx::query("UPDATE rules SET schedule = '{\"days\":\"someday\"}' WHERE id = ?")
+            .bind(&rule.id)
+            .execute(&pool)
+            .await?;
+        let err = store.get_rule(&rule.id).await.unwrap_err();
+        assert!(err.to_string().contains("rule 'test'"));
+        assert!(store.get_all_rules().await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migrates_old_rules_table() -> Result<()> {
+        let dir = tempdir()?;
+        let database_url = format!("sqlite:{}", dir.path().join("old.db").display());
+        DataStore::create_database(&database_url).await?;
+        let pool = SqlitePool::connect(&database_url).await?;
+
+        // Rules table as created before priorities and schedules existed
+        sqlx::query(
+            r#"
+            CREATE TABLE rules (
+                id TEXT PRIMARY KEY,
+                condition TEXT NOT NULL,
+                action TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            INSERT INTO rules VALUES ('old', '{"Keyword":"old"}', '"Filter"', 0, 0);
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        let store = DataStore::new(pool);
+        store.initialize().await?;
+        // Initializing again must not try to add the columns twice
+        store.initialize().await?;
+
+        use crate::content::{ActionType, ConditionType};
+        use crate::schedule::Schedule;
+
+        store
+            .save_rule(&Rule {
+                id: "new".to_string(),
+                condition: ConditionType::Keyword("new".to_string()),
+                action: ActionType::Filter,
+                priority: 5,
+                schedule: Some(Schedule::default()),
+            })
+            .await?;
+
+        let rules = store.get_all_rules().await?;
+        assert_eq!(rules.len(), 2);
+        let old = rules.iter().find(|r| r.id == "old").unwrap();
+        assert_eq!((old.priority, old.schedule.is_none()), (0, true));
+        let new = rules.iter().find(|r| r.id == "new").unwrap();
+        assert_eq!((new.priority, new.schedule.is_some()), (5, true));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_profiles() -> Result<()> {
+        let (_dir, _pool, store) = setup_test_db().await?;
+
+        use crate::content::{ConditionType, ActionType};
+
+        for id in ["sports", "social"] {
+            store.save_rule(&Rule {
+                id: id.to_string(),
+                condition: ConditionType::Keyword(id.to_string()),
+                action: ActionType::Filter,
+                priority: 0,
+                schedule: None,
+            }).await?;
+        }
+
+        store.create_profile("deep-work").await?;
+        store.add_rule_to_profile("deep-work", "sports").await?;
+        store.add_rule_to_profile("deep-work", "social").await?;
+        store.create_profile("evening").await?;
+        store.add_rule_to_profile("evening", "social").await?;
+
+        let rules = store.get_profile_rules("evening").await?;
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].id, "social");
+
+        let profiles = store.get_profiles().await?;
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].rule_ids, vec!["social", "sports"]);
+
+        store.set_active_profile(Some("evening")).await?;
+        assert_eq!(store.get_active_profile().await?.as_deref(), Some("evening"));
+        store.set_active_profile(None).await?;
+        assert!(store.get_active_profile().await?.is_none());
+
+        store.record_profile_attention("evening", "a", 1000).await?;
+        store.record_profile_attention("evening", "a", 500).await?;
+        store.record_profile_attention("evening", "b", 250).await?;
+        let metrics = store.get_profile_metrics().await?;
+        assert_eq!(metrics[0].total_duration, 1750);
+        assert_eq!(metrics[0].interactions, 3);
+        assert_eq!(metrics[0].items, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocklist_reimport() -> Result<()> {
+        let (_dir, _pool, store) = setup_test_db().await?;
+
+        use crate::blocklist::{parse_list, rules_for_list};
+        use crate::content::ActionType;
+
+        let first = parse_list("spoiler\nleak\n", ListFormat::Plain).conditions;
+        let rules = rules_for_list("spoilers", first, &ActionType::Filter);
+        assert_eq!(store.replace_blocklist("spoilers", "a.txt", ListFormat::Plain, &rules).await?, 0);
+
+        let second = parse_list("spoiler\nending\n", ListFormat::Plain).conditions;
+        let rules = rules_for_list("spoilers", second, &ActionType::Filter);
+        assert_eq!(store.replace_blocklist("spoilers", "a.txt", ListFormat::Plain, &rules).await?, 1);
+
+        let mut ids = store.get_blocklist_rule_ids("spoilers").await?;
+        ids.sort();
+        assert_eq!(ids, vec!["spoilers:keyword:ending", "spoilers:keyword:spoiler"]);
+        assert!(store.get_rule("spoilers:keyword:leak").await?.is_none());
+
+        let lists = store.get_blocklists().await?;
+        assert_eq!(lists.len(), 1);
+        assert_eq!(lists[0].rule_count, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fingerprints() -> Result<()> {
+        let (_dir, _pool, store) = setup_test_db().await?;
+
+        let seen = |id: &str, fingerprint: u64, days_ago: i64| Seen {
+            content_id: id.to_string(),
+            fingerprint,
+            seen_at: DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap() - chrono::Duration::days(days_ago),
+        };
+        store.record_fingerprint(&seen("old", 1, 10)).await?;
+        // The high bit survives SQLite's signed integers
+        store.record_fingerprint(&seen("new", u64::MAX - 1, 0)).await?;
+        store.record_fingerprint(&seen("new", u64::MAX - 1, 0)).await?;
+
+        let stored = store.get_fingerprints().await?;
+        assert_eq!(stored, vec![seen("old", 1, 10), seen("new", u64::MAX - 1, 0)]);
+
+        store.cleanup(7).await?;
+        assert_eq!(store.get_fingerprints().await?, vec![seen("new", u64::MAX - 1, 0)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_attention_events() -> Result<()> {
+        let (_dir, _pool, store) = setup_test_db().await?;
+
+        let now = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+        let event = |id: &str, days_ago: i64| AttentionEvent {
+            content_id: id.to_string(),
+            duration: 30_000,
+            at: now - chrono::Duration::days(days_ago),
+        };
+        store.record_attention_event(&event("old", 10)).await?;
+        store.record_attention_event(&event("recent", 2)).await?;
+        store.record_attention_event(&event("new", 0)).await?;
+
+        let since = now - chrono::Duration::days(30);
+        assert_eq!(
+            store.get_attention_events(since).await?,
+            vec![event("old", 10), event("recent", 2), event("new", 0)]
+        );
+        assert_eq!(store.get_attention_events(now).await?, vec![event("new", 0)]);
+
+        store.cleanup(7).await?;
+        assert_eq!(store.get_attention_events(since).await?, vec![event("recent", 2), event("new", 0)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queue() -> Result<()> {
+        let (_dir, _pool, store) = setup_test_db().await?;
+
+        let now = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+        let item = |id: &str, text: &str, queued_at: DateTime<Utc>| QueuedItem {
+            content: crate::content::Content {
+                id: id.to_string(),
+                text: text.to_string(),
+                view_duration: 0,
+                metadata: Default::default(),
+                flags: vec!["news".to_string()],
+                score: 0.0,
+            },
+            rule_id: "news".to_string(),
+            queued_at,
+            release_at: queued_at + chrono::Duration::hours(1),
+        };
+        store.queue_content(&item("a", "first", now - chrono::Duration::hours(2))).await?;
+        store.queue_content(&item("b", "second", now)).await?;
+
+        let due = store.get_queue(Some(now)).await?;
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].content.id, "a");
+        assert_eq!(store.get_queue(None).await?.len(), 2);
+
+        // Queueing again keeps the release time but stores the new copy
+        store.queue_content(&item("a", "first, edited", now)).await?;
+        let due = store.get_queue(Some(now)).await?;
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].content.text, "first, edited");
+        assert_eq!(due[0].release_at, now - chrono::Duration::hours(1));
+
+        store.release_queued(&["a".to_string()]).await?;
+        assert!(store.get_queue(Some(now)).await?.is_empty());
+        assert_eq!(store.get_queue(None).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reputation() -> Result<()> {
+        let (_dir, _pool, store) = setup_test_db().await?;
+
+        let now = Utc::now();
+        let read = ReputationUpdate { attention_ms: 60_000, ..Default::default() };
+        let filtered = ReputationUpdate { filtered: 1, ..Default::default() };
+
+        let alice = store.record_reputation("author", "alice", "post-1", &read, now).await?.unwrap();
+        assert_eq!(alice.attention_ms, 60_000);
+        let alice = store.record_reputation("author", "alice", "post-2", &filtered, now).await?.unwrap();
+        assert_eq!((alice.attention_ms, alice.filtered), (60_000, 1));
+
+        // The same item doesn't count twice
+        assert!(store.record_reputation("author", "alice", "post-2", &filtered, now).await?.is_none());
+        assert_eq!(store.get_reputation("author", "alice").await?.unwrap().filtered, 1);
+
+        store.set_reputation("author", "alice", Some(0.9)).await?;
+        store.set_reputation("source", "spam.example", Some(0.1)).await?;
+        let alice = store.get_reputation("author", "alice").await?.unwrap();
+        assert_eq!((alice.manual_score, alice.attention_ms), (Some(0.9), 60_000));
+        store.set_reputation("author", "alice", None).await?;
+        assert_eq!(store.get_reputation("author", "alice").await?.unwrap().manual_score, None);
+
+        assert_eq!(store.reset_reputation("author", Some("alice")).await?, 1);
+        assert!(store.get_reputation("author", "alice").await?.is_none());
+        // After a reset, items count again
+        assert!(store.record_reputation("author", "alice", "post-2", &filtered, now).await?.is_some());
+
+        assert_eq!(store.reset_reputation("source", None).await?, 1);
+        assert_eq!(store.get_reputations().await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rule_stats() -> Result<()> {
+        let (_dir, _pool, store) = setup_test_db().await?;
+
+        let evaluated = vec!["a".to_string(), "b".to_string()];
+        store.record_rule_evaluations(&evaluated).await?;
+        store.record_rule_evaluations(&evaluated[..1]).await?;
+        store.record_rule_match("a", true, false, false, 1500).await?;
+        store.record_rule_match("a", false, true, false, 0).await?;
+
+        let stats = store.get_rule_stats().await?;
+        let a = stats.iter().find(|s| s.rule_id == "a").unwrap();
+        assert_eq!((a.evaluated, a.matched, a.filtered, a.flagged), (2, 2, 1, 1));
+        assert_eq!(a.time_saved, 1500);
+        assert!(a.last_matched.is_some());
+
+        let b = stats.iter().find(|s| s.rule_id == "b").unwrap();
+        assert_eq!((b.evaluated, b.matched), (1, 0));
+        assert!(b.last_matched.is_none());
+
         Ok(())
     }
 }
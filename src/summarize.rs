@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+/// Metadata key holding the character count of the text before it was summarized
+pub const ORIGINAL_LENGTH_KEY: &str = "original_length";
+
+/// Common words that say nothing about what a sentence is about
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was", "one",
+    "our", "out", "has", "him", "his", "how", "its", "may", "new", "now", "own", "say", "she", "too",
+    "use", "who", "did", "get", "got", "let", "put", "they", "them", "then", "than", "that", "this",
+    "with", "from", "have", "been", "were", "will", "would", "could", "should", "there", "their",
+    "what", "when", "where", "which", "while", "about", "after", "also", "into", "just", "more",
+    "most", "some", "such", "only", "other", "over", "very", "your", "said", "says", "because",
+];
+
+/// Split text into sentences ending in `.`, `!` or `?` followed by whitespace, or at line breaks
+pub fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next_is_space = chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        if c == '\n' || (matches!(c, '.' | '!' | '?') && next_is_space) {
+            let end = i + c.len_utf8();
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+        }
+    }
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+fn terms(sentence: &str) -> impl Iterator<Item = String> + '_ {
+    sentence
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+}
+
+/// Extractive summary of the `count` most representative sentences, in their original order
+///
+/// Sentences score the average frequency of their terms across the whole text. Returns
+/// `None` when the text has no more than `count` sentences, as there is nothing to cut.
+pub fn summarize(text: &str, count: usize) -> Option<String> {
+    let sentences = sentences(text);
+    if sentences.len() <= count {
+        return None;
+    }
+
+    let mut frequencies: HashMap<String, f64> = HashMap::new();
+    for term in sentences.iter().flat_map(|s| terms(s)) {
+        *frequencies.entry(term).or_default() += 1.0;
+    }
+
+    let mut scored: Vec<(usize, f64)> = sentences
+        .iter()
+        .enumerate()
+        .map(|(i, sentence)| {
+            let terms: Vec<String> = terms(sentence).collect();
+            let total = terms.iter().map(|term| frequencies[term]).sum::<f64>();
+            (i, if terms.is_empty() { 0.0 } else { total / terms.len() as f64 })
+        })
+        .collect();
+    // Stable sort: equally scored sentences keep the earlier one
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut picked: Vec<usize> = scored.into_iter().take(count).map(|(i, _)| i).collect();
+    picked.sort_unstable();
+    Some(picked.into_iter().map(|i| sentences[i]).collect::<Vec<_>>().join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentences() {
+        assert_eq!(
+            sentences("Version 1.5 is out! Get it now.\nNotes follow? Yes"),
+            vec!["Version 1.5 is out!", "Get it now.", "Notes follow?", "Yes"]
+        );
+        assert!(sentences("  ").is_empty());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let text = "The river flooded the town overnight. Residents woke to water in their homes. \
+                    I had cereal for breakfast. The flooded river is expected to stay high, and the town \
+                    asks residents to avoid the river banks.";
+
+        let summary = summarize(text, 2).unwrap();
+        assert_eq!(
+            summary,
+            "The river flooded the town overnight. The flooded river is expected to stay high, and the \
+             town asks residents to avoid the river banks."
+        );
+        assert_eq!(summarize(text, 4), None);
+    }
+}
@@ -0,0 +1,190 @@
+//! Sandboxed WebAssembly condition plugins
+//!
+//! A plugin module has no imports and exports:
+//!
+//! - `memory`: its linear memory
+//! - `alloc(len: i32) -> i32`: reserve `len` bytes for the input and return their offset
+//! - `evaluate(ptr: i32, len: i32) -> i32`: non-zero if the input matches
+//!
+//! The input is the JSON object `{"content": <Content>, "config": <rule config>}`. Every
+//! evaluation runs in a fresh instance with bounded fuel and memory, so plugins cannot
+//! keep state between items or stall the pipeline.
+
+use crate::content::{ConditionPlugin, Content};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use std::path::Path;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Resource limits for one plugin evaluation
+#[derive(Debug, Clone, Copy)]
+pub struct WasmLimits {
+    /// Fuel available per evaluation, roughly one unit per instruction
+    pub fuel: u64,
+    /// Maximum linear memory size in bytes
+    pub max_memory: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 10_000_000,
+            max_memory: 16 << 20,
+        }
+    }
+}
+
+/// Compiled WASM condition plugin
+pub struct WasmPlugin {
+    engine: Engine,
+    module: Module,
+    limits: WasmLimits,
+}
+
+impl WasmPlugin {
+    /// Compile a plugin from WASM bytes
+    pub fn from_bytes(wasm: &[u8], limits: WasmLimits) -> Result<Self> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(|e| anyhow!("Invalid WASM module: {}", e))?;
+        Ok(Self { engine, module, limits })
+    }
+
+    /// Compile a plugin from a `.wasm` file
+    pub fn from_file(path: &Path, limits: WasmLimits) -> Result<Self> {
+        let wasm = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_bytes(&wasm, limits).with_context(|| format!("Failed to load {}", path.display()))
+    }
+
+    /// Run the plugin's `evaluate` export on raw input bytes
+    pub fn evaluate(&self, input: &[u8]) -> Result<bool> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.limits.max_memory)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store: Store<StoreLimits> = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.add_fuel(self.limits.fuel).map_err(|e| anyhow!("{}", e))?;
+
+        // No host functions are linked, so the module can only compute on its input
+        let instance = Linker::<StoreLimits>::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| anyhow!("Failed to instantiate WASM plugin: {}", e))?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .context("WASM plugin does not export `memory`")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| anyhow!("WASM plugin has no valid `alloc` export: {}", e))?;
+        let evaluate = instance
+            .get_typed_func::<(i32, i32), i32>(&store, "evaluate")
+            .map_err(|e| anyhow!("WASM plugin has no valid `evaluate` export: {}", e))?;
+
+        let len = i32::try_from(input.len()).context("WASM plugin input too large")?;
+        let ptr = alloc
+            .call(&mut store, len)
+            .map_err(|e| anyhow!("WASM plugin `alloc` failed: {}", e))?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|e| anyhow!("WASM plugin returned an invalid input buffer: {}", e))?;
+
+        let result = evaluate
+            .call(&mut store, (ptr, len))
+            .map_err(|e| anyhow!("WASM plugin `evaluate` failed: {}", e))?;
+        Ok(result != 0)
+    }
+}
+
+#[async_trait]
+impl ConditionPlugin for WasmPlugin {
+    async fn matches(&self, content: &Content, config: &serde_json::Value) -> Result<bool> {
+        let input = serde_json::to_vec(&serde_json::json!({ "content": content, "config": config }))?;
+        self.evaluate(&input)
+    }
+}
+
+/// Load every `.wasm` file in `dir`, named by file stem
+pub fn load_dir(dir: &Path, limits: WasmLimits) -> Result<Vec<(String, WasmPlugin)>> {
+    let mut plugins = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "wasm") {
+            let Some(name) = path.file_stem() else { continue };
+            plugins.push((name.to_string_lossy().into_owned(), WasmPlugin::from_file(&path, limits)?));
+        }
+    }
+    Ok(plugins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Matches when the input contains `!`; `spin` and `grow` misbehave on purpose
+    const FIXTURE: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (local.get $ptr) (local.get $len)))
+            (local.get $ptr))
+          (func (export "evaluate") (param $ptr i32) (param $len i32) (result i32)
+            (local $end i32)
+            (local.set $end (i32.add (local.get $ptr) (local.get $len)))
+            (block $done
+              (loop $scan
+                (br_if $done (i32.ge_u (local.get $ptr) (local.get $end)))
+                (if (i32.eq (i32.load8_u (local.get $ptr)) (i32.const 33))
+                  (then (return (i32.const 1))))
+                (local.set $ptr (i32.add (local.get $ptr) (i32.const 1)))
+                (br $scan)))
+            (i32.const 0))
+          (func (export "spin") (param i32 i32) (result i32)
+            (loop $forever (br $forever))
+            (i32.const 0))
+          (func (export "grow") (param i32 i32) (result i32)
+            (memory.grow (i32.const 1024))))
+    "#;
+
+    fn content(text: &str) -> Content {
+        Content {
+            id: "test".to_string(),
+            text: text.to_string(),
+            view_duration: 0,
+            metadata: HashMap::new(),
+            flags: vec![],
+            score: 0.0,
+        }
+    }
+
+    /// The fixture with `export` renamed to serve as `evaluate`
+    fn plugin_with_evaluate(export: &str) -> WasmPlugin {
+        let wat = FIXTURE
+            .replace(r#"(export "evaluate")"#, r#"(export "scan")"#)
+            .replace(&format!(r#"(export "{}")"#, export), r#"(export "evaluate")"#);
+        WasmPlugin::from_bytes(&wat::parse_str(wat).unwrap(), WasmLimits::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_evaluate() -> Result<()> {
+        let plugin = WasmPlugin::from_bytes(&wat::parse_str(FIXTURE)?, WasmLimits::default())?;
+
+        assert!(plugin.matches(&content("You won't believe this!"), &serde_json::Value::Null).await?);
+        assert!(!plugin.matches(&content("A calm headline"), &serde_json::Value::Null).await?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_limits() {
+        let err = plugin_with_evaluate("spin").evaluate(b"{}").unwrap_err();
+        assert!(err.to_string().contains("fuel"), "{}", err);
+
+        assert!(plugin_with_evaluate("grow").evaluate(b"{}").is_err());
+    }
+}